@@ -7,6 +7,9 @@ use serde::{Deserialize, Serialize};
 #[sea_orm(table_name = "history")]
 #[serde(rename_all = "camelCase")]
 pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    #[serde(skip)]
+    pub user_id: i32,
     #[sea_orm(primary_key, auto_increment = false)]
     pub comic_id: String,
     pub chapter_id: String,
@@ -19,6 +22,21 @@ pub struct Model {
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
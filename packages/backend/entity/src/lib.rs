@@ -2,5 +2,18 @@
 
 pub mod prelude;
 
+pub mod api_token;
+pub mod chapter_read;
+pub mod collection;
+pub mod collection_comic;
+pub mod comic_meta;
+pub mod comic_note;
+pub mod comic_tag;
 pub mod history;
 pub mod lib_comic;
+pub mod login_attempt;
+pub mod read_event;
+pub mod session;
+pub mod setting;
+pub mod share_link;
+pub mod user;
@@ -7,15 +7,58 @@ use serde::{Deserialize, Serialize};
 #[sea_orm(table_name = "lib_comic")]
 #[serde(rename_all = "camelCase")]
 pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    #[serde(skip)]
+    pub user_id: i32,
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: String,
     pub name: String,
     pub cover: String,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
+    pub site: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::collection_comic::Entity")]
+    CollectionComic,
+    #[sea_orm(has_one = "super::comic_note::Entity")]
+    ComicNote,
+    #[sea_orm(has_many = "super::comic_tag::Entity")]
+    ComicTag,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::collection_comic::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CollectionComic.def()
+    }
+}
+
+impl Related<super::comic_note::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ComicNote.def()
+    }
+}
+
+impl Related<super::comic_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ComicTag.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
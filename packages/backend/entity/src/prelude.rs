@@ -1,4 +1,17 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.4
 
+pub use super::api_token::Entity as ApiToken;
+pub use super::chapter_read::Entity as ChapterRead;
+pub use super::collection::Entity as Collection;
+pub use super::collection_comic::Entity as CollectionComic;
+pub use super::comic_meta::Entity as ComicMeta;
+pub use super::comic_note::Entity as ComicNote;
+pub use super::comic_tag::Entity as ComicTag;
 pub use super::history::Entity as History;
 pub use super::lib_comic::Entity as LibComic;
+pub use super::login_attempt::Entity as LoginAttempt;
+pub use super::read_event::Entity as ReadEvent;
+pub use super::session::Entity as Session;
+pub use super::setting::Entity as Setting;
+pub use super::share_link::Entity as ShareLink;
+pub use super::user::Entity as User;
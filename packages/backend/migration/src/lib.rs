@@ -2,6 +2,19 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
 mod m20250122_140251_library;
+mod m20250208_093012_comic_meta;
+mod m20250215_201533_chapter_read;
+mod m20250222_161045_read_event;
+mod m20250301_110824_collection;
+mod m20250308_142236_library_progress;
+mod m20250315_090517_comic_note;
+mod m20250322_103418_user;
+mod m20250329_094127_session_token;
+mod m20250405_152308_login_attempt;
+mod m20250412_201745_api_token;
+mod m20250419_143052_share_link;
+mod m20250426_101530_chapter_read_marked;
+mod m20250503_094620_comic_meta_site;
 
 pub struct Migrator;
 
@@ -11,6 +24,19 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20220101_000001_create_table::Migration),
             Box::new(m20250122_140251_library::Migration),
+            Box::new(m20250208_093012_comic_meta::Migration),
+            Box::new(m20250215_201533_chapter_read::Migration),
+            Box::new(m20250222_161045_read_event::Migration),
+            Box::new(m20250301_110824_collection::Migration),
+            Box::new(m20250308_142236_library_progress::Migration),
+            Box::new(m20250315_090517_comic_note::Migration),
+            Box::new(m20250322_103418_user::Migration),
+            Box::new(m20250329_094127_session_token::Migration),
+            Box::new(m20250405_152308_login_attempt::Migration),
+            Box::new(m20250412_201745_api_token::Migration),
+            Box::new(m20250419_143052_share_link::Migration),
+            Box::new(m20250426_101530_chapter_read_marked::Migration),
+            Box::new(m20250503_094620_comic_meta_site::Migration),
         ]
     }
 }
@@ -0,0 +1,55 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ComicMeta::Table)
+                    .if_not_exists()
+                    .col(string(ComicMeta::Id).primary_key())
+                    .col(string(ComicMeta::Name))
+                    .col(string(ComicMeta::Cover))
+                    .col(json(ComicMeta::Author))
+                    .col(string(ComicMeta::Status))
+                    .col(json(ComicMeta::Genres))
+                    .col(string(ComicMeta::Region))
+                    .col(json(ComicMeta::Aliases))
+                    .col(double_null(ComicMeta::Rating))
+                    .col(string(ComicMeta::LastChapterName))
+                    .col(string(ComicMeta::LastUpdate))
+                    .col(timestamp(ComicMeta::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(ComicMeta::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ComicMeta::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComicMeta {
+    Table,
+    Id,
+    Name,
+    Cover,
+    Author,
+    Status,
+    Genres,
+    Region,
+    Aliases,
+    Rating,
+    LastChapterName,
+    LastUpdate,
+    CreatedAt,
+    UpdatedAt,
+}
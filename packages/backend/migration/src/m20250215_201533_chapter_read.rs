@@ -0,0 +1,63 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChapterRead::Table)
+                    .if_not_exists()
+                    .col(string(ChapterRead::ComicId))
+                    .col(string(ChapterRead::ChapterId))
+                    .col(string(ChapterRead::ChapterName))
+                    .col(integer(ChapterRead::Page))
+                    .col(integer(ChapterRead::TotalPages))
+                    .col(boolean(ChapterRead::Completed))
+                    .col(timestamp(ChapterRead::ReadAt).default(Expr::current_timestamp()))
+                    .col(timestamp(ChapterRead::CreatedAt).default(Expr::current_timestamp()))
+                    .primary_key(
+                        Index::create()
+                            .col(ChapterRead::ComicId)
+                            .col(ChapterRead::ChapterId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // keep the chapters recorded by the per-comic history as in progress
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT OR IGNORE INTO "chapter_read"
+                    ("comic_id", "chapter_id", "chapter_name", "page", "total_pages", "completed", "read_at", "created_at")
+                SELECT "comic_id", "chapter_id", "chapter_name", "page", 0, false, "updated_at", "created_at"
+                FROM "history""#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ChapterRead::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChapterRead {
+    Table,
+    ComicId,
+    ChapterId,
+    ChapterName,
+    Page,
+    TotalPages,
+    Completed,
+    ReadAt,
+    CreatedAt,
+}
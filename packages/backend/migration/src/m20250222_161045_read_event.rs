@@ -0,0 +1,57 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReadEvent::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ReadEvent::Id))
+                    .col(string(ReadEvent::ComicId))
+                    .col(string(ReadEvent::ChapterId))
+                    .col(string(ReadEvent::ComicName))
+                    .col(string(ReadEvent::ChapterName))
+                    .col(integer(ReadEvent::Page))
+                    .col(boolean(ReadEvent::Completed))
+                    .col(integer(ReadEvent::Duration))
+                    .col(timestamp(ReadEvent::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_read_event_created_at")
+                    .table(ReadEvent::Table)
+                    .col(ReadEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReadEvent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ReadEvent {
+    Table,
+    Id,
+    ComicId,
+    ChapterId,
+    ComicName,
+    ChapterName,
+    Page,
+    Completed,
+    Duration,
+    CreatedAt,
+}
@@ -0,0 +1,87 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Collection::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Collection::Id))
+                    .col(string_uniq(Collection::Name))
+                    .col(integer(Collection::SortOrder))
+                    .col(timestamp(Collection::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(Collection::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(CollectionComic::Table)
+                    .if_not_exists()
+                    .col(integer(CollectionComic::CollectionId))
+                    .col(string(CollectionComic::ComicId))
+                    .col(integer(CollectionComic::SortOrder))
+                    .col(timestamp(CollectionComic::CreatedAt).default(Expr::current_timestamp()))
+                    .primary_key(
+                        Index::create()
+                            .col(CollectionComic::CollectionId)
+                            .col(CollectionComic::ComicId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CollectionComic::Table, CollectionComic::CollectionId)
+                            .to(Collection::Table, Collection::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(CollectionComic::Table, CollectionComic::ComicId)
+                            .to(LibComic::Table, LibComic::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CollectionComic::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Collection::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Collection {
+    Table,
+    Id,
+    Name,
+    SortOrder,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum CollectionComic {
+    Table,
+    CollectionId,
+    ComicId,
+    SortOrder,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LibComic {
+    Table,
+    Id,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite only supports one column per `ALTER TABLE`
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LibComic::Table)
+                    .add_column(string(LibComic::Site).default("manhuagui"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComicMeta::Table)
+                    .add_column(integer(ComicMeta::ChapterCount).default(0))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ComicMeta::Table)
+                    .drop_column(ComicMeta::ChapterCount)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LibComic::Table)
+                    .drop_column(LibComic::Site)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LibComic {
+    Table,
+    Site,
+}
+
+#[derive(DeriveIden)]
+enum ComicMeta {
+    Table,
+    ChapterCount,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ComicNote::Table)
+                    .if_not_exists()
+                    .col(string(ComicNote::ComicId).primary_key())
+                    .col(integer_null(ComicNote::Rating))
+                    .col(text(ComicNote::Notes))
+                    .col(timestamp(ComicNote::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(ComicNote::UpdatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ComicNote::Table, ComicNote::ComicId)
+                            .to(LibComic::Table, LibComic::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ComicTag::Table)
+                    .if_not_exists()
+                    .col(string(ComicTag::ComicId))
+                    .col(string(ComicTag::Tag))
+                    .col(timestamp(ComicTag::CreatedAt).default(Expr::current_timestamp()))
+                    .primary_key(Index::create().col(ComicTag::ComicId).col(ComicTag::Tag))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ComicTag::Table, ComicTag::ComicId)
+                            .to(LibComic::Table, LibComic::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ComicTag::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ComicNote::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ComicNote {
+    Table,
+    ComicId,
+    Rating,
+    Notes,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ComicTag {
+    Table,
+    ComicId,
+    Tag,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LibComic {
+    Table,
+    Id,
+}
@@ -0,0 +1,458 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Id of the admin account existing data is assigned to.
+const ADMIN_ID: i32 = 1;
+
+/// Tables scoped by user, parents before children.
+const SCOPED_TABLES: [&str; 8] = [
+    "lib_comic",
+    "history",
+    "chapter_read",
+    "read_event",
+    "collection",
+    "collection_comic",
+    "comic_note",
+    "comic_tag",
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(User::Table)
+                    .if_not_exists()
+                    .col(pk_auto(User::Id))
+                    .col(string_uniq(User::Username))
+                    // empty until a password is set on startup
+                    .col(string(User::PasswordHash))
+                    .col(string(User::Role).default("member"))
+                    .col(timestamp(User::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(User::UpdatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(User::Table)
+                    .columns([User::Id, User::Username, User::PasswordHash, User::Role])
+                    .values_panic([ADMIN_ID.into(), "admin".into(), "".into(), "admin".into()])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Session::Table)
+                    .if_not_exists()
+                    .col(string(Session::Id).primary_key())
+                    .col(integer(Session::UserId))
+                    .col(timestamp(Session::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(&mut user_foreign_key(Session::Table, Session::UserId))
+                    .to_owned(),
+            )
+            .await?;
+
+        // sqlite cannot change primary keys in place, so every scoped table is
+        // rebuilt. Renaming first keeps foreign keys between the old tables
+        // intact, and dropping children first avoids cascading deletes.
+        for table in SCOPED_TABLES {
+            manager
+                .rename_table(
+                    Table::rename()
+                        .table(Alias::new(table), Alias::new(format!("{table}_old")))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        create_scoped_tables(manager).await?;
+
+        let db = manager.get_connection();
+        for table in SCOPED_TABLES {
+            let columns = columns_of(table);
+            db.execute_unprepared(&format!(
+                r#"INSERT INTO "{table}" ("user_id", {columns}) SELECT {ADMIN_ID}, {columns} FROM "{table}_old""#
+            ))
+            .await?;
+        }
+
+        for table in SCOPED_TABLES.into_iter().rev() {
+            manager
+                .drop_table(
+                    Table::drop()
+                        .table(Alias::new(format!("{table}_old")))
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_read_event_user_id_created_at")
+                    .table(ReadEvent::Table)
+                    .col(ReadEvent::UserId)
+                    .col(ReadEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_collection_user_id_name")
+                    .table(Collection::Table)
+                    .col(Collection::UserId)
+                    .col(Collection::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Migration(
+            "Splitting data per user cannot be reverted".to_string(),
+        ))
+    }
+}
+
+fn columns_of(table: &str) -> &'static str {
+    match table {
+        "lib_comic" => r#""id", "name", "cover", "created_at", "updated_at", "site""#,
+        "history" => {
+            r#""comic_id", "chapter_id", "comic_name", "chapter_name", "page", "visible", "created_at", "updated_at""#
+        }
+        "chapter_read" => {
+            r#""comic_id", "chapter_id", "chapter_name", "page", "total_pages", "completed", "read_at", "created_at""#
+        }
+        "read_event" => {
+            r#""id", "comic_id", "chapter_id", "comic_name", "chapter_name", "page", "completed", "duration", "created_at""#
+        }
+        "collection" => r#""id", "name", "sort_order", "created_at", "updated_at""#,
+        "collection_comic" => r#""collection_id", "comic_id", "sort_order", "created_at""#,
+        "comic_note" => r#""comic_id", "rating", "notes", "created_at", "updated_at""#,
+        "comic_tag" => r#""comic_id", "tag", "created_at""#,
+        _ => unreachable!(),
+    }
+}
+
+fn user_foreign_key<T: IntoIden + 'static>(
+    table: T,
+    column: impl IntoIden + 'static,
+) -> ForeignKeyCreateStatement {
+    ForeignKey::create()
+        .from(table, column)
+        .to(User::Table, User::Id)
+        .on_delete(ForeignKeyAction::Cascade)
+        .to_owned()
+}
+
+fn lib_comic_foreign_key<T: IntoIden + 'static>(
+    table: T,
+    user_id: impl IntoIden + 'static,
+    comic_id: impl IntoIden + 'static,
+) -> ForeignKeyCreateStatement {
+    ForeignKey::create()
+        .from(table, (user_id, comic_id))
+        .to(LibComic::Table, (LibComic::UserId, LibComic::Id))
+        .on_delete(ForeignKeyAction::Cascade)
+        .to_owned()
+}
+
+async fn create_scoped_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(LibComic::Table)
+                .col(integer(LibComic::UserId))
+                .col(string(LibComic::Id))
+                .col(string(LibComic::Name))
+                .col(string(LibComic::Cover))
+                .col(timestamp(LibComic::CreatedAt).default(Expr::current_timestamp()))
+                .col(timestamp(LibComic::UpdatedAt).default(Expr::current_timestamp()))
+                .col(string(LibComic::Site).default("manhuagui"))
+                .primary_key(Index::create().col(LibComic::UserId).col(LibComic::Id))
+                .foreign_key(&mut user_foreign_key(LibComic::Table, LibComic::UserId))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_table(
+            Table::create()
+                .table(History::Table)
+                .col(integer(History::UserId))
+                .col(string(History::ComicId))
+                .col(string(History::ChapterId))
+                .col(string(History::ComicName))
+                .col(string(History::ChapterName))
+                .col(unsigned(History::Page))
+                .col(boolean(History::Visible))
+                .col(timestamp(History::CreatedAt).default(Expr::current_timestamp()))
+                .col(timestamp(History::UpdatedAt).default(Expr::current_timestamp()))
+                .primary_key(Index::create().col(History::UserId).col(History::ComicId))
+                .foreign_key(&mut user_foreign_key(History::Table, History::UserId))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_table(
+            Table::create()
+                .table(ChapterRead::Table)
+                .col(integer(ChapterRead::UserId))
+                .col(string(ChapterRead::ComicId))
+                .col(string(ChapterRead::ChapterId))
+                .col(string(ChapterRead::ChapterName))
+                .col(integer(ChapterRead::Page))
+                .col(integer(ChapterRead::TotalPages))
+                .col(boolean(ChapterRead::Completed))
+                .col(timestamp(ChapterRead::ReadAt).default(Expr::current_timestamp()))
+                .col(timestamp(ChapterRead::CreatedAt).default(Expr::current_timestamp()))
+                .primary_key(
+                    Index::create()
+                        .col(ChapterRead::UserId)
+                        .col(ChapterRead::ComicId)
+                        .col(ChapterRead::ChapterId),
+                )
+                .foreign_key(&mut user_foreign_key(
+                    ChapterRead::Table,
+                    ChapterRead::UserId,
+                ))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_table(
+            Table::create()
+                .table(ReadEvent::Table)
+                .col(pk_auto(ReadEvent::Id))
+                .col(integer(ReadEvent::UserId))
+                .col(string(ReadEvent::ComicId))
+                .col(string(ReadEvent::ChapterId))
+                .col(string(ReadEvent::ComicName))
+                .col(string(ReadEvent::ChapterName))
+                .col(integer(ReadEvent::Page))
+                .col(boolean(ReadEvent::Completed))
+                .col(integer(ReadEvent::Duration))
+                .col(timestamp(ReadEvent::CreatedAt).default(Expr::current_timestamp()))
+                .foreign_key(&mut user_foreign_key(ReadEvent::Table, ReadEvent::UserId))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_table(
+            Table::create()
+                .table(Collection::Table)
+                .col(pk_auto(Collection::Id))
+                .col(integer(Collection::UserId))
+                .col(string(Collection::Name))
+                .col(integer(Collection::SortOrder))
+                .col(timestamp(Collection::CreatedAt).default(Expr::current_timestamp()))
+                .col(timestamp(Collection::UpdatedAt).default(Expr::current_timestamp()))
+                .foreign_key(&mut user_foreign_key(Collection::Table, Collection::UserId))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_table(
+            Table::create()
+                .table(CollectionComic::Table)
+                .col(integer(CollectionComic::CollectionId))
+                .col(integer(CollectionComic::UserId))
+                .col(string(CollectionComic::ComicId))
+                .col(integer(CollectionComic::SortOrder))
+                .col(timestamp(CollectionComic::CreatedAt).default(Expr::current_timestamp()))
+                .primary_key(
+                    Index::create()
+                        .col(CollectionComic::CollectionId)
+                        .col(CollectionComic::ComicId),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .from(CollectionComic::Table, CollectionComic::CollectionId)
+                        .to(Collection::Table, Collection::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(&mut lib_comic_foreign_key(
+                    CollectionComic::Table,
+                    CollectionComic::UserId,
+                    CollectionComic::ComicId,
+                ))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_table(
+            Table::create()
+                .table(ComicNote::Table)
+                .col(integer(ComicNote::UserId))
+                .col(string(ComicNote::ComicId))
+                .col(integer_null(ComicNote::Rating))
+                .col(text(ComicNote::Notes))
+                .col(timestamp(ComicNote::CreatedAt).default(Expr::current_timestamp()))
+                .col(timestamp(ComicNote::UpdatedAt).default(Expr::current_timestamp()))
+                .primary_key(
+                    Index::create()
+                        .col(ComicNote::UserId)
+                        .col(ComicNote::ComicId),
+                )
+                .foreign_key(&mut lib_comic_foreign_key(
+                    ComicNote::Table,
+                    ComicNote::UserId,
+                    ComicNote::ComicId,
+                ))
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_table(
+            Table::create()
+                .table(ComicTag::Table)
+                .col(integer(ComicTag::UserId))
+                .col(string(ComicTag::ComicId))
+                .col(string(ComicTag::Tag))
+                .col(timestamp(ComicTag::CreatedAt).default(Expr::current_timestamp()))
+                .primary_key(
+                    Index::create()
+                        .col(ComicTag::UserId)
+                        .col(ComicTag::ComicId)
+                        .col(ComicTag::Tag),
+                )
+                .foreign_key(&mut lib_comic_foreign_key(
+                    ComicTag::Table,
+                    ComicTag::UserId,
+                    ComicTag::ComicId,
+                ))
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    Username,
+    PasswordHash,
+    Role,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Session {
+    Table,
+    Id,
+    UserId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LibComic {
+    Table,
+    UserId,
+    Id,
+    Name,
+    Cover,
+    CreatedAt,
+    UpdatedAt,
+    Site,
+}
+
+#[derive(DeriveIden)]
+enum History {
+    Table,
+    UserId,
+    ComicId,
+    ChapterId,
+    ComicName,
+    ChapterName,
+    Page,
+    Visible,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ChapterRead {
+    Table,
+    UserId,
+    ComicId,
+    ChapterId,
+    ChapterName,
+    Page,
+    TotalPages,
+    Completed,
+    ReadAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ReadEvent {
+    Table,
+    Id,
+    UserId,
+    ComicId,
+    ChapterId,
+    ComicName,
+    ChapterName,
+    Page,
+    Completed,
+    Duration,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Collection {
+    Table,
+    Id,
+    UserId,
+    Name,
+    SortOrder,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum CollectionComic {
+    Table,
+    CollectionId,
+    UserId,
+    ComicId,
+    SortOrder,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ComicNote {
+    Table,
+    UserId,
+    ComicId,
+    Rating,
+    Notes,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ComicTag {
+    Table,
+    UserId,
+    ComicId,
+    Tag,
+    CreatedAt,
+}
@@ -0,0 +1,111 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Setting::Table)
+                    .if_not_exists()
+                    .col(string(Setting::Key).primary_key())
+                    .col(string(Setting::Value))
+                    .to_owned(),
+            )
+            .await?;
+
+        // sessions used to be keyed by the raw token, so existing ones are
+        // dropped rather than migrated, logging everyone out once
+        manager
+            .drop_table(Table::drop().table(Session::Table).to_owned())
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(Session::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Session::Id))
+                    .col(string_uniq(Session::TokenHash))
+                    .col(integer(Session::UserId))
+                    .col(string(Session::UserAgent).default(""))
+                    .col(string(Session::Ip).default(""))
+                    .col(timestamp(Session::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(Session::LastSeenAt).default(Expr::current_timestamp()))
+                    .col(timestamp(Session::ExpiresAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Session::Table, Session::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_session_user_id")
+                    .table(Session::Table)
+                    .col(Session::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Session::Table).to_owned())
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(Session::Table)
+                    .if_not_exists()
+                    .col(string(Session::Id).primary_key())
+                    .col(integer(Session::UserId))
+                    .col(timestamp(Session::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Session::Table, Session::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Setting::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Setting {
+    Table,
+    Key,
+    Value,
+}
+
+#[derive(DeriveIden)]
+enum Session {
+    Table,
+    Id,
+    TokenHash,
+    UserId,
+    UserAgent,
+    Ip,
+    CreatedAt,
+    LastSeenAt,
+    ExpiresAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
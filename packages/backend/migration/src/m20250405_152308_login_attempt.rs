@@ -0,0 +1,60 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempt::Table)
+                    .if_not_exists()
+                    .col(pk_auto(LoginAttempt::Id))
+                    .col(string(LoginAttempt::Username))
+                    .col(string(LoginAttempt::Ip))
+                    .col(string(LoginAttempt::Result))
+                    .col(timestamp(LoginAttempt::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_attempt_username_created_at")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::Username)
+                    .col(LoginAttempt::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_attempt_ip_created_at")
+                    .table(LoginAttempt::Table)
+                    .col(LoginAttempt::Ip)
+                    .col(LoginAttempt::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginAttempt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginAttempt {
+    Table,
+    Id,
+    Username,
+    Ip,
+    Result,
+    CreatedAt,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ApiToken::Id))
+                    .col(integer(ApiToken::UserId))
+                    .col(string(ApiToken::Name))
+                    .col(string_uniq(ApiToken::TokenHash))
+                    // shown in listings so tokens can be told apart
+                    .col(string(ApiToken::Prefix))
+                    .col(json(ApiToken::Scopes))
+                    .col(timestamp(ApiToken::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp_null(ApiToken::LastUsedAt))
+                    .col(timestamp_null(ApiToken::ExpiresAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ApiToken::Table, ApiToken::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_api_token_user_id")
+                    .table(ApiToken::Table)
+                    .col(ApiToken::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApiToken::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiToken {
+    Table,
+    Id,
+    UserId,
+    Name,
+    TokenHash,
+    Prefix,
+    Scopes,
+    CreatedAt,
+    LastUsedAt,
+    ExpiresAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShareLink::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ShareLink::Id))
+                    .col(integer(ShareLink::UserId))
+                    .col(string_uniq(ShareLink::TokenHash))
+                    .col(string(ShareLink::ComicId))
+                    .col(string(ShareLink::ComicName))
+                    // the whole comic is shared when null
+                    .col(string_null(ShareLink::ChapterId))
+                    .col(string_null(ShareLink::ChapterName))
+                    .col(timestamp(ShareLink::CreatedAt).default(Expr::current_timestamp()))
+                    .col(timestamp(ShareLink::ExpiresAt))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ShareLink::Table, ShareLink::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_share_link_user_id")
+                    .table(ShareLink::Table)
+                    .col(ShareLink::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShareLink::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ShareLink {
+    Table,
+    Id,
+    UserId,
+    TokenHash,
+    ComicId,
+    ComicName,
+    ChapterId,
+    ChapterName,
+    CreatedAt,
+    ExpiresAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
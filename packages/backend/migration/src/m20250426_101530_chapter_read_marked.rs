@@ -0,0 +1,37 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // chapters marked as read in bulk rather than opened, which reading
+        // stats leave out
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChapterRead::Table)
+                    .add_column(boolean(ChapterRead::Marked).default(false))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChapterRead::Table)
+                    .drop_column(ChapterRead::Marked)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChapterRead {
+    Table,
+    Marked,
+}
@@ -0,0 +1,118 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const COLUMNS: &str = r#""id", "name", "cover", "author", "status", "genres", "region", "aliases", "rating", "last_chapter_name", "last_update", "created_at", "updated_at", "chapter_count""#;
+
+/// Cached metadata is keyed by site as well, since comic ids are only unique
+/// within a site. sqlite cannot change a primary key, so the table is
+/// rebuilt.
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        create_table(
+            manager,
+            Some(Index::create().col(ComicMeta::Site).col(ComicMeta::Id)),
+        )
+        .await?;
+
+        // manhuagui was the only site so far
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                r#"INSERT INTO "comic_meta_new" ("site", {COLUMNS})
+                SELECT 'manhuagui', {COLUMNS} FROM "comic_meta""#
+            ))
+            .await?;
+
+        replace_table(manager).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        create_table(manager, None).await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                r#"INSERT OR IGNORE INTO "comic_meta_new" ({COLUMNS})
+                SELECT {COLUMNS} FROM "comic_meta" WHERE "site" = 'manhuagui'"#
+            ))
+            .await?;
+
+        replace_table(manager).await
+    }
+}
+
+/// Keyed by `id` alone without `key`, as before this migration.
+async fn create_table(
+    manager: &SchemaManager<'_>,
+    key: Option<&mut IndexCreateStatement>,
+) -> Result<(), DbErr> {
+    let mut table = Table::create();
+    table.table(ComicMetaNew::Table);
+    match key {
+        Some(key) => {
+            table
+                .col(string(ComicMeta::Site))
+                .col(string(ComicMeta::Id))
+                .primary_key(key);
+        }
+        None => {
+            table.col(string(ComicMeta::Id).primary_key());
+        }
+    }
+    table
+        .col(string(ComicMeta::Name))
+        .col(string(ComicMeta::Cover))
+        .col(json(ComicMeta::Author))
+        .col(string(ComicMeta::Status))
+        .col(json(ComicMeta::Genres))
+        .col(string(ComicMeta::Region))
+        .col(json(ComicMeta::Aliases))
+        .col(double_null(ComicMeta::Rating))
+        .col(string(ComicMeta::LastChapterName))
+        .col(string(ComicMeta::LastUpdate))
+        .col(timestamp(ComicMeta::CreatedAt).default(Expr::current_timestamp()))
+        .col(timestamp(ComicMeta::UpdatedAt).default(Expr::current_timestamp()))
+        .col(integer(ComicMeta::ChapterCount).default(0));
+    manager.create_table(table.to_owned()).await
+}
+
+async fn replace_table(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    manager
+        .drop_table(Table::drop().table(ComicMeta::Table).to_owned())
+        .await?;
+    manager
+        .rename_table(
+            Table::rename()
+                .table(ComicMetaNew::Table, ComicMeta::Table)
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(DeriveIden)]
+enum ComicMeta {
+    Table,
+    Site,
+    Id,
+    Name,
+    Cover,
+    Author,
+    Status,
+    Genres,
+    Region,
+    Aliases,
+    Rating,
+    LastChapterName,
+    LastUpdate,
+    CreatedAt,
+    UpdatedAt,
+    ChapterCount,
+}
+
+#[derive(DeriveIden)]
+enum ComicMetaNew {
+    Table,
+}
@@ -0,0 +1,205 @@
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, bail, Result};
+use argon2::{
+    password_hash::{rand_core::OsRng, SaltString},
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+};
+use axum_extra::extract::cookie::Key;
+use chrono::TimeDelta;
+use clap::ValueEnum;
+use entity::{api_token, user};
+use rand::{distributions::Alphanumeric, Rng};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::db;
+
+pub const SESSION_COOKIE: &str = "comiya-session";
+
+/// How often the last use of a session or API token is written back.
+pub const TOUCH_INTERVAL: TimeDelta = TimeDelta::minutes(5);
+
+const COOKIE_KEY_SETTING: &str = "cookie_key";
+
+/// Makes API tokens recognizable, e.g. to secret scanners.
+const API_TOKEN_PREFIX: &str = "cmy_";
+
+/// Characters of a token kept in plain text to tell tokens apart.
+const API_TOKEN_VISIBLE_LEN: usize = 8;
+
+pub const MAX_API_TOKEN_DAYS: u64 = 3650;
+
+/// Created by the migration that introduced accounts, and given the data
+/// that existed before.
+pub const DEFAULT_ADMIN: &str = "admin";
+
+const MIN_PASSWORD_LEN: usize = 8;
+
+/// Ordered by what a role is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "camelCase")]
+pub enum Role {
+    /// Can search and read, but has no library or history of its own.
+    Guest,
+    Member,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Guest => "guest",
+            Role::Member => "member",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Unknown roles get the least access.
+    pub fn of(user: &user::Model) -> Role {
+        match user.role.as_str() {
+            "admin" => Role::Admin,
+            "member" => Role::Member,
+            _ => Role::Guest,
+        }
+    }
+}
+
+/// What an API token may access. Sessions are not limited by scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Scope {
+    /// Browsing sites, fetching chapter pages and images, and reading the
+    /// library, history and stats.
+    Read,
+    /// Changing the library, collections, notes and reading progress.
+    Library,
+    /// Exporting backups.
+    Downloads,
+    /// Managing users, for admins only.
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Library => "library",
+            Scope::Downloads => "downloads",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+pub fn token_scopes(token: &api_token::Model) -> Vec<Scope> {
+    serde_json::from_value(token.scopes.clone()).unwrap_or_default()
+}
+
+pub fn validate_password(password: &str) -> Result<()> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        bail!("Password must be at least {MIN_PASSWORD_LEN} characters");
+    }
+    Ok(())
+}
+
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|err| anyhow!("Failed to hash password: {err}"))?;
+    Ok(hash.to_string())
+}
+
+/// Accounts without a password hash cannot log in.
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(hash) else {
+        return false;
+    };
+    Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok()
+}
+
+/// Verified against when a username does not exist, so failed logins take
+/// as long whether or not the account exists.
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password(&random_string(16)).unwrap_or_default());
+
+pub fn verify_login(user: Option<user::Model>, password: &str) -> Option<user::Model> {
+    match user {
+        Some(user) => verify_password(password, &user.password_hash).then_some(user),
+        None => {
+            verify_password(password, &DUMMY_HASH);
+            None
+        }
+    }
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+pub fn new_password() -> String {
+    random_string(16)
+}
+
+pub fn new_session_token() -> String {
+    random_string(48)
+}
+
+pub fn new_share_token() -> String {
+    random_string(32)
+}
+
+/// Returns the token along with the part of it shown in listings.
+pub fn new_api_token() -> (String, String) {
+    let token = format!("{API_TOKEN_PREFIX}{}", random_string(40));
+    let visible = token[..API_TOKEN_PREFIX.len() + API_TOKEN_VISIBLE_LEN].to_string();
+    (token, visible)
+}
+
+/// Sessions and API tokens are stored by hash, so a leaked database cannot
+/// be used to log in.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Loads the key session cookies are signed with, generating it on first
+/// start.
+pub async fn load_cookie_key(db: &DatabaseConnection) -> Result<Key> {
+    if let Some(value) = db::get_setting(db, COOKIE_KEY_SETTING).await? {
+        let bytes = hex::decode(value)?;
+        return Key::try_from(bytes.as_slice()).map_err(|err| anyhow!("Invalid cookie key: {err}"));
+    }
+
+    let key = Key::generate();
+    db::set_setting(db, COOKIE_KEY_SETTING, &hex::encode(key.master())).await?;
+    Ok(key)
+}
+
+/// Sets a password for accounts created without one, i.e. the admin that
+/// existing data was assigned to. The configured password is used when set,
+/// so upgraded instances keep their password, otherwise a random one is
+/// logged once.
+pub async fn init_passwords(db: &DatabaseConnection, configured: Option<&str>) -> Result<()> {
+    for user in db::get_users_without_password(db).await? {
+        let password = match configured {
+            None => {
+                let password = new_password();
+                tracing::warn!(
+                    "created password `{password}` for user `{}`, change it after logging in",
+                    user.username
+                );
+                password
+            }
+            Some(configured) => configured.to_string(),
+        };
+        db::set_user_password(db, user.id, &hash_password(&password)?).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use entity::{
+    chapter_read, collection, collection_comic, comic_note, comic_tag, history, lib_comic,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    IntoActiveModel, QueryFilter, QueryOrder, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever the archive layout changes in a way older versions of
+/// the importer cannot read.
+pub const BACKUP_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    #[serde(default)]
+    pub library: Vec<lib_comic::Model>,
+    #[serde(default)]
+    pub collections: Vec<collection::Model>,
+    #[serde(default)]
+    pub collection_comics: Vec<collection_comic::Model>,
+    #[serde(default)]
+    pub history: Vec<history::Model>,
+    #[serde(default)]
+    pub chapter_reads: Vec<chapter_read::Model>,
+    #[serde(default)]
+    pub notes: Vec<comic_note::Model>,
+    #[serde(default)]
+    pub tags: Vec<comic_tag::Model>,
+}
+
+/// How to resolve rows that exist both in the archive and the database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStrategy {
+    Skip,
+    Overwrite,
+    #[default]
+    NewestWins,
+}
+
+impl MergeStrategy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "skip" => Some(MergeStrategy::Skip),
+            "overwrite" => Some(MergeStrategy::Overwrite),
+            "newestWins" | "newest-wins" | "newest" => Some(MergeStrategy::NewestWins),
+            _ => None,
+        }
+    }
+
+    fn should_write(&self, existing: Option<DateTime<Utc>>, incoming: DateTime<Utc>) -> bool {
+        match (self, existing) {
+            (_, None) => true,
+            (MergeStrategy::Skip, Some(_)) => false,
+            (MergeStrategy::Overwrite, Some(_)) => true,
+            (MergeStrategy::NewestWins, Some(existing)) => incoming > existing,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCount {
+    pub written: usize,
+    pub skipped: usize,
+}
+
+impl ImportCount {
+    pub fn add(&mut self, written: bool) {
+        if written {
+            self.written += 1;
+        } else {
+            self.skipped += 1;
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub library: ImportCount,
+    pub collections: ImportCount,
+    pub collection_comics: ImportCount,
+    pub history: ImportCount,
+    pub chapter_reads: ImportCount,
+    pub notes: ImportCount,
+    pub tags: ImportCount,
+}
+
+/// Exports the data of a single user. Archives carry no user ids, so they
+/// can be imported into any account.
+pub async fn export_backup(db: &DatabaseConnection, user_id: i32) -> Result<Backup> {
+    Ok(Backup {
+        version: BACKUP_VERSION,
+        exported_at: Utc::now(),
+        library: lib_comic::Entity::find()
+            .filter(lib_comic::Column::UserId.eq(user_id))
+            .order_by_asc(lib_comic::Column::CreatedAt)
+            .all(db)
+            .await?,
+        collections: collection::Entity::find()
+            .filter(collection::Column::UserId.eq(user_id))
+            .order_by_asc(collection::Column::SortOrder)
+            .all(db)
+            .await?,
+        collection_comics: collection_comic::Entity::find()
+            .filter(collection_comic::Column::UserId.eq(user_id))
+            .all(db)
+            .await?,
+        history: history::Entity::find()
+            .filter(history::Column::UserId.eq(user_id))
+            .all(db)
+            .await?,
+        chapter_reads: chapter_read::Entity::find()
+            .filter(chapter_read::Column::UserId.eq(user_id))
+            .all(db)
+            .await?,
+        notes: comic_note::Entity::find()
+            .filter(comic_note::Column::UserId.eq(user_id))
+            .all(db)
+            .await?,
+        tags: comic_tag::Entity::find()
+            .filter(comic_tag::Column::UserId.eq(user_id))
+            .all(db)
+            .await?,
+    })
+}
+
+/// Imports an archive in a single transaction, so a failing row leaves the
+/// database untouched.
+pub async fn import_backup(
+    db: &DatabaseConnection,
+    user_id: i32,
+    backup: Backup,
+    strategy: MergeStrategy,
+) -> Result<ImportSummary> {
+    if backup.version > BACKUP_VERSION {
+        bail!(
+            "Backup version {} is newer than the supported version {BACKUP_VERSION}",
+            backup.version
+        );
+    }
+
+    let mut summary = ImportSummary::default();
+    let txn = db.begin().await?;
+
+    for mut item in backup.library {
+        item.user_id = user_id;
+        let existing = lib_comic::Entity::find_by_id((user_id, item.id.clone()))
+            .one(&txn)
+            .await?;
+        let write = strategy.should_write(existing.as_ref().map(|e| e.updated_at), item.updated_at);
+        if write {
+            save(&txn, item.into_active_model(), existing.is_some()).await?;
+        }
+        summary.library.add(write);
+    }
+
+    // collection ids are local to each instance, so they are matched by name
+    let mut collection_ids = HashMap::new();
+    for mut item in backup.collections {
+        item.user_id = user_id;
+        let existing = collection::Entity::find()
+            .filter(collection::Column::UserId.eq(user_id))
+            .filter(collection::Column::Name.eq(&item.name))
+            .one(&txn)
+            .await?;
+        let write = strategy.should_write(existing.as_ref().map(|e| e.updated_at), item.updated_at);
+
+        let backup_id = item.id;
+        let id = match existing {
+            Some(existing) => {
+                if write {
+                    let mut model = item.into_active_model().reset_all();
+                    model.id = sea_orm::Set(existing.id);
+                    model.update(&txn).await?;
+                }
+                existing.id
+            }
+            None => {
+                let mut model = item.into_active_model().reset_all();
+                model.id = sea_orm::NotSet;
+                model.insert(&txn).await?.id
+            }
+        };
+        collection_ids.insert(backup_id, id);
+        summary.collections.add(write);
+    }
+
+    for mut item in backup.collection_comics {
+        let Some(&collection_id) = collection_ids.get(&item.collection_id) else {
+            summary.collection_comics.add(false);
+            continue;
+        };
+        item.collection_id = collection_id;
+        item.user_id = user_id;
+
+        let existing =
+            collection_comic::Entity::find_by_id((item.collection_id, item.comic_id.clone()))
+                .one(&txn)
+                .await?;
+        let in_library = lib_comic::Entity::find_by_id((user_id, item.comic_id.clone()))
+            .one(&txn)
+            .await?
+            .is_some();
+        // memberships carry no update time, so only overwriting replaces them
+        let write = in_library && (existing.is_none() || strategy == MergeStrategy::Overwrite);
+        if write {
+            save(&txn, item.into_active_model(), existing.is_some()).await?;
+        }
+        summary.collection_comics.add(write);
+    }
+
+    for mut item in backup.history {
+        item.user_id = user_id;
+        let existing = history::Entity::find_by_id((user_id, item.comic_id.clone()))
+            .one(&txn)
+            .await?;
+        let write = strategy.should_write(existing.as_ref().map(|e| e.updated_at), item.updated_at);
+        if write {
+            save(&txn, item.into_active_model(), existing.is_some()).await?;
+        }
+        summary.history.add(write);
+    }
+
+    for mut item in backup.chapter_reads {
+        item.user_id = user_id;
+        let existing = chapter_read::Entity::find_by_id((
+            user_id,
+            item.comic_id.clone(),
+            item.chapter_id.clone(),
+        ))
+        .one(&txn)
+        .await?;
+        let write = strategy.should_write(existing.as_ref().map(|e| e.read_at), item.read_at);
+        if write {
+            save(&txn, item.into_active_model(), existing.is_some()).await?;
+        }
+        summary.chapter_reads.add(write);
+    }
+
+    for mut item in backup.notes {
+        item.user_id = user_id;
+        let existing = comic_note::Entity::find_by_id((user_id, item.comic_id.clone()))
+            .one(&txn)
+            .await?;
+        let in_library = lib_comic::Entity::find_by_id((user_id, item.comic_id.clone()))
+            .one(&txn)
+            .await?
+            .is_some();
+        let write = in_library
+            && strategy.should_write(existing.as_ref().map(|e| e.updated_at), item.updated_at);
+        if write {
+            save(&txn, item.into_active_model(), existing.is_some()).await?;
+        }
+        summary.notes.add(write);
+    }
+
+    // tags are merged as sets, except that overwriting replaces the tags of
+    // every comic present in the archive
+    if strategy == MergeStrategy::Overwrite {
+        let mut comic_ids = backup
+            .tags
+            .iter()
+            .map(|item| item.comic_id.clone())
+            .collect::<Vec<_>>();
+        comic_ids.sort();
+        comic_ids.dedup();
+        comic_tag::Entity::delete_many()
+            .filter(comic_tag::Column::UserId.eq(user_id))
+            .filter(comic_tag::Column::ComicId.is_in(comic_ids))
+            .exec(&txn)
+            .await?;
+    }
+    for mut item in backup.tags {
+        item.user_id = user_id;
+        let existing =
+            comic_tag::Entity::find_by_id((user_id, item.comic_id.clone(), item.tag.clone()))
+                .one(&txn)
+                .await?;
+        let in_library = lib_comic::Entity::find_by_id((user_id, item.comic_id.clone()))
+            .one(&txn)
+            .await?
+            .is_some();
+        let write = in_library && existing.is_none();
+        if write {
+            save(&txn, item.into_active_model(), false).await?;
+        }
+        summary.tags.add(write);
+    }
+
+    txn.commit().await?;
+    Ok(summary)
+}
+
+async fn save<A>(txn: &DatabaseTransaction, model: A, exists: bool) -> Result<()>
+where
+    A: ActiveModelTrait + sea_orm::ActiveModelBehavior + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+{
+    let model = model.reset_all();
+    if exists {
+        model.update(txn).await?;
+    } else {
+        model.insert(txn).await?;
+    }
+    Ok(())
+}
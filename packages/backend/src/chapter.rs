@@ -0,0 +1,320 @@
+use std::{cmp::Ordering, sync::LazyLock};
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::site::{ComicChapterBrief, ComicChapterGroup};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChapterKind {
+    #[default]
+    Chapter,
+    Volume,
+    Extra,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChapterNumber {
+    pub kind: ChapterKind,
+    pub number: Option<f64>,
+}
+
+impl ChapterNumber {
+    /// Zero padded label used when numbering exported files, e.g. `Ch.012.5`,
+    /// `Vol.03` or `Extra.01`.
+    pub fn export_label(&self) -> Option<String> {
+        // rounded as a whole, so `12.999` becomes `13` rather than `12.1`
+        let hundredths = (self.number? * 100.0).round() as u64;
+        let integer = hundredths / 100;
+        let fraction = match hundredths % 100 {
+            0 => String::new(),
+            fraction => format!(".{fraction:02}").trim_end_matches('0').to_string(),
+        };
+
+        Some(match self.kind {
+            ChapterKind::Chapter => format!("Ch.{integer:03}{fraction}"),
+            ChapterKind::Volume => format!("Vol.{integer:02}{fraction}"),
+            ChapterKind::Extra => format!("Extra.{integer:02}{fraction}"),
+        })
+    }
+}
+
+const NUMBER: &str = r"([0-9]+(?:\.[0-9]+)?|[零〇一二两三四五六七八九十百千]+)";
+
+static EXTRA_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)番外|特别|特別|外传|外傳|短篇|附录|附錄|特典|\bsp\b|\bextra\b").unwrap()
+});
+
+static UNIT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!(r"第?\s*{NUMBER}\s*(话|話|回|章|集|卷|册|冊)")).unwrap());
+
+static PREFIX_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?i)(第|vol\.?|ch\.?|chapter|episode|ep\.?)\s*{NUMBER}"
+    ))
+    .unwrap()
+});
+
+static ARABIC_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[0-9]+(?:\.[0-9]+)?").unwrap());
+
+/// Extracts the kind and number of a chapter from its name, e.g. `第12话`,
+/// `第3卷` or `番外01`. The group name is used as a hint for names that only
+/// carry a bare number, like the `单行本` group of manhuagui.
+pub fn parse_chapter_number(name: &str, group_name: &str) -> ChapterNumber {
+    let mut kind = if group_name.contains("番外") {
+        ChapterKind::Extra
+    } else if group_name.contains('卷') || group_name.contains("单行本") {
+        ChapterKind::Volume
+    } else {
+        ChapterKind::Chapter
+    };
+
+    if EXTRA_RE.is_match(name) {
+        kind = ChapterKind::Extra;
+    }
+
+    if let Some(caps) = UNIT_RE.captures(name) {
+        if kind != ChapterKind::Extra {
+            kind = match &caps[2] {
+                "卷" | "册" | "冊" => ChapterKind::Volume,
+                _ => ChapterKind::Chapter,
+            };
+        }
+        return ChapterNumber {
+            kind,
+            number: parse_number(&caps[1]),
+        };
+    }
+
+    if let Some(caps) = PREFIX_RE.captures(name) {
+        if kind != ChapterKind::Extra && caps[1].to_lowercase().starts_with("vol") {
+            kind = ChapterKind::Volume;
+        }
+        return ChapterNumber {
+            kind,
+            number: parse_number(&caps[2]),
+        };
+    }
+
+    ChapterNumber {
+        kind,
+        number: ARABIC_RE
+            .find(name)
+            .and_then(|m| m.as_str().parse::<f64>().ok()),
+    }
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .or_else(|| parse_chinese_number(value))
+}
+
+fn parse_chinese_number(value: &str) -> Option<f64> {
+    let mut section = 0_u64;
+    let mut digit = None;
+
+    for c in value.chars() {
+        match c {
+            '零' | '〇' => digit = Some(0),
+            '一' => digit = Some(1),
+            '二' | '两' => digit = Some(2),
+            '三' => digit = Some(3),
+            '四' => digit = Some(4),
+            '五' => digit = Some(5),
+            '六' => digit = Some(6),
+            '七' => digit = Some(7),
+            '八' => digit = Some(8),
+            '九' => digit = Some(9),
+            '十' | '百' | '千' => {
+                let unit = match c {
+                    '十' => 10,
+                    '百' => 100,
+                    _ => 1000,
+                };
+                // `十二` omits the leading `一`
+                section += digit.take().unwrap_or(1) * unit;
+            }
+            _ => return None,
+        }
+    }
+
+    Some((section + digit.unwrap_or(0)) as f64)
+}
+
+/// Sorts the chapters of every group by their parsed number. Groups with any
+/// chapter whose number could not be parsed keep the upstream order, since
+/// mixing both orderings would scramble them.
+pub fn sort_chapter_groups(groups: &mut [ComicChapterGroup]) {
+    for group in groups {
+        if group
+            .chapters
+            .iter()
+            .any(|chapter| chapter.number.is_none())
+        {
+            continue;
+        }
+        group.chapters.sort_by(compare_chapters);
+    }
+}
+
+fn compare_chapters(a: &ComicChapterBrief, b: &ComicChapterBrief) -> Ordering {
+    let kind_order = |kind: ChapterKind| match kind {
+        ChapterKind::Volume => 0,
+        ChapterKind::Chapter => 1,
+        ChapterKind::Extra => 2,
+    };
+
+    kind_order(a.kind)
+        .cmp(&kind_order(b.kind))
+        .then(a.number.partial_cmp(&b.number).unwrap_or(Ordering::Equal))
+}
+
+/// Returns the chapter following `last_read_id` within its group, or the
+/// first chapter of the comic when nothing has been read yet.
+pub fn next_unread<'a>(
+    groups: &'a [ComicChapterGroup],
+    last_read_id: Option<&str>,
+) -> Option<&'a ComicChapterBrief> {
+    let Some(last_read_id) = last_read_id else {
+        return groups.iter().flat_map(|group| &group.chapters).next();
+    };
+
+    groups.iter().find_map(|group| {
+        let index = group
+            .chapters
+            .iter()
+            .position(|chapter| chapter.id == last_read_id)?;
+        group.chapters.get(index + 1)
+    })
+}
+
+pub fn find_chapter<'a>(
+    groups: &'a [ComicChapterGroup],
+    chapter_id: &str,
+) -> Option<&'a ComicChapterBrief> {
+    groups
+        .iter()
+        .flat_map(|group| &group.chapters)
+        .find(|chapter| chapter.id == chapter_id)
+}
+
+/// Chapters of a comic to act on at once.
+#[derive(Debug, Deserialize)]
+#[serde(
+    tag = "scope",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ChapterScope {
+    Chapter {
+        chapter_id: String,
+    },
+    /// Chapters ordered before the given one within its group.
+    Before {
+        chapter_id: String,
+        #[serde(default)]
+        inclusive: bool,
+    },
+    Group {
+        group_name: String,
+    },
+    Comic,
+}
+
+pub fn select_chapters<'a>(
+    groups: &'a [ComicChapterGroup],
+    scope: &ChapterScope,
+) -> Result<Vec<&'a ComicChapterBrief>> {
+    let list = match scope {
+        ChapterScope::Chapter { chapter_id } => vec![find_chapter(groups, chapter_id)
+            .ok_or_else(|| anyhow!("Chapter {chapter_id} not found"))?],
+        ChapterScope::Before {
+            chapter_id,
+            inclusive,
+        } => groups
+            .iter()
+            .find_map(|group| {
+                let index = group
+                    .chapters
+                    .iter()
+                    .position(|chapter| &chapter.id == chapter_id)?;
+                let end = if *inclusive { index + 1 } else { index };
+                Some(group.chapters[..end].iter().collect())
+            })
+            .ok_or_else(|| anyhow!("Chapter {chapter_id} not found"))?,
+        ChapterScope::Group { group_name } => groups
+            .iter()
+            .find(|group| &group.name == group_name)
+            .map(|group| group.chapters.iter().collect())
+            .ok_or_else(|| anyhow!("Chapter group {group_name} not found"))?,
+        ChapterScope::Comic => groups.iter().flat_map(|group| &group.chapters).collect(),
+    };
+
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(kind: ChapterKind, number: f64) -> ChapterNumber {
+        ChapterNumber {
+            kind,
+            number: Some(number),
+        }
+    }
+
+    #[test]
+    fn parses_chapter_numbers() {
+        use ChapterKind::*;
+
+        assert_eq!(parse_chapter_number("第12话", ""), number(Chapter, 12.0));
+        assert_eq!(parse_chapter_number("第12.5話", ""), number(Chapter, 12.5));
+        assert_eq!(parse_chapter_number("第3卷", ""), number(Volume, 3.0));
+        assert_eq!(parse_chapter_number("第十二回", ""), number(Chapter, 12.0));
+        assert_eq!(parse_chapter_number("Vol.2", ""), number(Volume, 2.0));
+        assert_eq!(parse_chapter_number("Chapter 7", ""), number(Chapter, 7.0));
+        assert_eq!(parse_chapter_number("番外01", ""), number(Extra, 1.0));
+        assert_eq!(parse_chapter_number("番外第2话", ""), number(Extra, 2.0));
+        assert_eq!(parse_chapter_number("05", "单行本"), number(Volume, 5.0));
+        assert_eq!(parse_chapter_number("05", "番外篇"), number(Extra, 5.0));
+        assert_eq!(
+            parse_chapter_number("特别篇", ""),
+            ChapterNumber {
+                kind: Extra,
+                number: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_chinese_numbers() {
+        assert_eq!(parse_chinese_number("七"), Some(7.0));
+        assert_eq!(parse_chinese_number("十"), Some(10.0));
+        assert_eq!(parse_chinese_number("十二"), Some(12.0));
+        assert_eq!(parse_chinese_number("二十"), Some(20.0));
+        assert_eq!(parse_chinese_number("一百零五"), Some(105.0));
+        assert_eq!(parse_chinese_number("两千三百"), Some(2300.0));
+        assert_eq!(parse_chinese_number("〇"), Some(0.0));
+        assert_eq!(parse_chinese_number("十二话"), None);
+    }
+
+    #[test]
+    fn formats_export_labels() {
+        use ChapterKind::*;
+
+        assert_eq!(number(Chapter, 12.0).export_label().unwrap(), "Ch.012");
+        assert_eq!(number(Chapter, 12.5).export_label().unwrap(), "Ch.012.5");
+        assert_eq!(number(Chapter, 12.25).export_label().unwrap(), "Ch.012.25");
+        assert_eq!(number(Chapter, 12.999).export_label().unwrap(), "Ch.013");
+        assert_eq!(number(Chapter, 1234.0).export_label().unwrap(), "Ch.1234");
+        assert_eq!(number(Volume, 3.0).export_label().unwrap(), "Vol.03");
+        assert_eq!(number(Extra, 1.05).export_label().unwrap(), "Extra.01.05");
+        assert_eq!(ChapterNumber::default().export_label(), None);
+    }
+}
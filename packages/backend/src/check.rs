@@ -0,0 +1,105 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::time::{timeout, Instant};
+
+use crate::site::SiteId;
+
+/// For all steps of a check together.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteStatus {
+    pub site: SiteId,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Outcome of the latest scrapes of a site since the process started,
+/// covering every page fetched through [`SiteId`] but not images.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteHealth {
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    /// Error of the last failure, kept after later successes.
+    pub last_error: Option<String>,
+}
+
+static SITE_HEALTH: LazyLock<Mutex<HashMap<SiteId, SiteHealth>>> = LazyLock::new(Default::default);
+
+/// Called by the [`SiteId`] methods with the result of each scrape.
+pub fn record<T>(site: SiteId, res: &Result<T>) {
+    let mut map = SITE_HEALTH.lock().unwrap_or_else(|err| err.into_inner());
+    let health = map.entry(site).or_default();
+    match res {
+        Ok(_) => health.last_success_at = Some(Utc::now()),
+        Err(err) => {
+            health.last_failure_at = Some(Utc::now());
+            health.last_error = Some(err.to_string());
+        }
+    }
+}
+
+pub fn site_health(site: SiteId) -> SiteHealth {
+    let map = SITE_HEALTH.lock().unwrap_or_else(|err| err.into_inner());
+    map.get(&site).cloned().unwrap_or_default()
+}
+
+/// Searched for when checking a site, and expected to have results.
+fn check_keyword(site: SiteId) -> &'static str {
+    match site {
+        SiteId::Manhuagui => "海贼王",
+    }
+}
+
+/// Goes through searching, opening a comic and reading its first chapter,
+/// so changes to a site's pages show up as parse errors.
+async fn check(site: SiteId) -> Result<()> {
+    let keyword = check_keyword(site);
+    let comic = site
+        .search_comic(keyword.to_string())
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Searching for {keyword} returned no comics"))?;
+
+    let comic = site.get_comic(comic.id).await?;
+    let chapter = comic
+        .chapter_groups
+        .iter()
+        .flat_map(|group| &group.chapters)
+        .next()
+        .ok_or_else(|| anyhow!("Comic {} has no chapters", comic.id))?;
+
+    let chapter = site
+        .get_chapter(comic.id.clone(), chapter.id.clone())
+        .await?;
+    if chapter.images.is_empty() {
+        return Err(anyhow!("Chapter {} has no images", chapter.id));
+    }
+    Ok(())
+}
+
+pub async fn check_site(site: SiteId) -> SiteStatus {
+    let start = Instant::now();
+    let error = match timeout(CHECK_TIMEOUT, check(site)).await {
+        Ok(Ok(())) => None,
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!("Timed out after {}s", CHECK_TIMEOUT.as_secs())),
+    };
+    SiteStatus {
+        site,
+        ok: error.is_none(),
+        error,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::TimeDelta;
+use clap::Args;
+use serde::Deserialize;
+
+use crate::{server::TrustedProxies, site::SiteId};
+
+/// Read when `--config` is not given, if it exists.
+const DEFAULT_CONFIG_PATH: &str = "data/comiya.toml";
+
+const MAX_SESSION_TTL_DAYS: u32 = 3650;
+
+/// Settings are read from a TOML file, then overridden by env vars, then by
+/// command line flags. Anything left unset keeps its default.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub database_url: String,
+    pub bind: SocketAddr,
+    /// Where the built frontend is served from.
+    pub static_dir: PathBuf,
+    /// Every site is enabled when empty.
+    pub enabled_sites: Vec<String>,
+    /// How long in-flight requests may take to finish when shutting down.
+    pub shutdown_timeout_secs: u64,
+    pub auth: AuthConfig,
+    pub http: HttpConfig,
+    pub cache: CacheConfig,
+    pub scheduler: SchedulerConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Initial password of the admin account, a random one is logged when
+    /// unset.
+    pub password: Option<String>,
+    /// Addresses or CIDR ranges whose `X-Forwarded-For` header is honored.
+    pub trusted_proxies: Vec<String>,
+    pub session_ttl_days: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// For whole requests to sites, including reading the body.
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Image URLs remembered per share link.
+    pub shared_images: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchedulerConfig {
+    /// How often expired sessions, share links and old login attempts are
+    /// deleted.
+    pub cleanup_interval_secs: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: "sqlite://data/comiya.db?mode=rwc".to_string(),
+            bind: SocketAddr::from(([0, 0, 0, 0], 8000)),
+            static_dir: PathBuf::from("dist"),
+            enabled_sites: vec![],
+            shutdown_timeout_secs: 10,
+            auth: AuthConfig::default(),
+            http: HttpConfig::default(),
+            cache: CacheConfig::default(),
+            scheduler: SchedulerConfig::default(),
+        }
+    }
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            password: None,
+            trusted_proxies: vec![],
+            session_ttl_days: 30,
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            connect_timeout_secs: 10,
+            user_agent: None,
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            shared_images: 2000,
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            cleanup_interval_secs: 60 * 60,
+        }
+    }
+}
+
+/// Command line flags and env vars, each overriding its setting in the
+/// config file.
+#[derive(Debug, Default, Args)]
+pub struct ConfigArgs {
+    /// TOML config file [default: data/comiya.toml, if it exists]
+    #[arg(long, env = "COMIYA_CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+    #[arg(long, env = "DATABASE_URL", global = true)]
+    pub database_url: Option<String>,
+    #[arg(long, env = "COMIYA_BIND", global = true)]
+    pub bind: Option<SocketAddr>,
+    #[arg(long, env = "COMIYA_STATIC_DIR", global = true)]
+    pub static_dir: Option<PathBuf>,
+    #[arg(long, env = "ENABLED_SITES", value_delimiter = ',', global = true)]
+    pub enabled_sites: Option<Vec<String>>,
+    #[arg(long, env = "COMIYA_SHUTDOWN_TIMEOUT_SECS", global = true)]
+    pub shutdown_timeout_secs: Option<u64>,
+    /// Initial password of the admin account
+    #[arg(
+        long = "admin-password",
+        env = "PASSWORD",
+        hide_env_values = true,
+        global = true
+    )]
+    pub password: Option<String>,
+    #[arg(long, env = "TRUSTED_PROXIES", value_delimiter = ',', global = true)]
+    pub trusted_proxies: Option<Vec<String>>,
+    #[arg(long, env = "COMIYA_SESSION_TTL_DAYS", global = true)]
+    pub session_ttl_days: Option<u32>,
+    #[arg(long, env = "COMIYA_HTTP_TIMEOUT_SECS", global = true)]
+    pub http_timeout_secs: Option<u64>,
+    #[arg(long, env = "COMIYA_HTTP_CONNECT_TIMEOUT_SECS", global = true)]
+    pub http_connect_timeout_secs: Option<u64>,
+    #[arg(long, env = "COMIYA_HTTP_USER_AGENT", global = true)]
+    pub http_user_agent: Option<String>,
+    #[arg(long, env = "COMIYA_SHARED_IMAGES_CACHE", global = true)]
+    pub shared_images_cache: Option<usize>,
+    #[arg(long, env = "COMIYA_CLEANUP_INTERVAL_SECS", global = true)]
+    pub cleanup_interval_secs: Option<u64>,
+}
+
+/// Empty values, e.g. `TRUSTED_PROXIES=`, count as unset.
+fn non_empty(values: Vec<String>) -> Vec<String> {
+    values
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+impl Config {
+    pub fn load(args: ConfigArgs) -> Result<Self> {
+        let mut config = match &args.config {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+        config.apply(args);
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| anyhow!("Failed to read config file {}: {err}", path.display()))?;
+        toml::from_str(&content)
+            .map_err(|err| anyhow!("Invalid config file {}: {err}", path.display()))
+    }
+
+    fn apply(&mut self, args: ConfigArgs) {
+        let ConfigArgs {
+            config: _,
+            database_url,
+            bind,
+            static_dir,
+            enabled_sites,
+            shutdown_timeout_secs,
+            password,
+            trusted_proxies,
+            session_ttl_days,
+            http_timeout_secs,
+            http_connect_timeout_secs,
+            http_user_agent,
+            shared_images_cache,
+            cleanup_interval_secs,
+        } = args;
+
+        if let Some(value) = database_url {
+            self.database_url = value;
+        }
+        if let Some(value) = bind {
+            self.bind = value;
+        }
+        if let Some(value) = static_dir {
+            self.static_dir = value;
+        }
+        if let Some(value) = enabled_sites {
+            self.enabled_sites = value;
+        }
+        if let Some(value) = shutdown_timeout_secs {
+            self.shutdown_timeout_secs = value;
+        }
+        if let Some(value) = password {
+            self.auth.password = Some(value);
+        }
+        if let Some(value) = trusted_proxies {
+            self.auth.trusted_proxies = value;
+        }
+        if let Some(value) = session_ttl_days {
+            self.auth.session_ttl_days = value;
+        }
+        if let Some(value) = http_timeout_secs {
+            self.http.timeout_secs = value;
+        }
+        if let Some(value) = http_connect_timeout_secs {
+            self.http.connect_timeout_secs = value;
+        }
+        if let Some(value) = http_user_agent {
+            self.http.user_agent = Some(value);
+        }
+        if let Some(value) = shared_images_cache {
+            self.cache.shared_images = value;
+        }
+        if let Some(value) = cleanup_interval_secs {
+            self.scheduler.cleanup_interval_secs = value;
+        }
+
+        self.enabled_sites = non_empty(std::mem::take(&mut self.enabled_sites));
+        self.auth.trusted_proxies = non_empty(std::mem::take(&mut self.auth.trusted_proxies));
+        self.auth.password = self
+            .auth
+            .password
+            .take()
+            .filter(|password| !password.trim().is_empty());
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.database_url.trim().is_empty() {
+            bail!("`database_url` cannot be empty");
+        }
+        for site in &self.enabled_sites {
+            if SiteId::parse(site).is_none() {
+                bail!("Unknown site `{site}` in `enabled_sites`");
+            }
+        }
+        TrustedProxies::parse(&self.auth.trusted_proxies)
+            .map_err(|err| anyhow!("Invalid `auth.trusted_proxies`: {err}"))?;
+        if !(1..=MAX_SESSION_TTL_DAYS).contains(&self.auth.session_ttl_days) {
+            bail!("`auth.session_ttl_days` must be between 1 and {MAX_SESSION_TTL_DAYS}");
+        }
+        if self.http.timeout_secs == 0 || self.http.connect_timeout_secs == 0 {
+            bail!("`http.timeout_secs` and `http.connect_timeout_secs` must be at least 1");
+        }
+        if self.cache.shared_images == 0 {
+            bail!("`cache.shared_images` must be at least 1");
+        }
+        if self.scheduler.cleanup_interval_secs == 0 {
+            bail!("`scheduler.cleanup_interval_secs` must be at least 1");
+        }
+        if !self.static_dir.is_dir() {
+            tracing::warn!(
+                "static dir {} does not exist, only the API is served",
+                self.static_dir.display()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn enabled_sites(&self) -> Vec<SiteId> {
+        let sites = self
+            .enabled_sites
+            .iter()
+            .filter_map(|site| SiteId::parse(site))
+            .collect::<Vec<_>>();
+
+        if sites.is_empty() {
+            SiteId::ALL.to_vec()
+        } else {
+            sites
+        }
+    }
+
+    pub fn session_ttl(&self) -> TimeDelta {
+        TimeDelta::days(self.auth.session_ttl_days.into())
+    }
+
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(self.shutdown_timeout_secs)
+    }
+
+    pub fn cleanup_interval(&self) -> Duration {
+        Duration::from_secs(self.scheduler.cleanup_interval_secs)
+    }
+}
@@ -1,14 +1,53 @@
-use anyhow::Result;
-use entity::{history, lib_comic};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use entity::{
+    api_token, chapter_read, collection, collection_comic, comic_meta, comic_note, comic_tag,
+    history, lib_comic, login_attempt, read_event, session, setting, share_link, user,
+};
+use migration::{Migrator, MigratorTrait};
 use sea_orm::{
-    sea_query::{self, OnConflict},
-    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+    sea_query::{self, Expr, OnConflict},
+    ColumnTrait, Condition, ConnectionTrait, Database, DatabaseConnection, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait,
+};
+
+use crate::{
+    site::{Comic, PageList, SiteId},
+    throttle::LoginResult,
 };
 
+/// Connects to the database and applies pending migrations.
+pub async fn connect(url: &str) -> Result<DatabaseConnection> {
+    let db = Database::connect(url).await?;
+    migrate(&db).await?;
+    Ok(db)
+}
+
+/// Applies pending migrations, returning their names.
+pub async fn migrate(db: &DatabaseConnection) -> Result<Vec<String>> {
+    let pending = Migrator::get_pending_migrations(db)
+        .await?
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+    Migrator::up(db, None).await?;
+    Ok(pending)
+}
+
+/// Fails when the database cannot be reached or migrations are pending.
+pub async fn check_ready(db: &DatabaseConnection) -> Result<()> {
+    db.ping().await?;
+    let pending = Migrator::get_pending_migrations(db).await?;
+    if !pending.is_empty() {
+        bail!("{} migrations are pending", pending.len());
+    }
+    Ok(())
+}
+
 pub async fn upsert_history(db: &DatabaseConnection, item: history::ActiveModel) -> Result<()> {
     history::Entity::insert(item)
         .on_conflict(
-            OnConflict::column(history::Column::ComicId)
+            OnConflict::columns([history::Column::UserId, history::Column::ComicId])
                 .update_columns([
                     history::Column::ChapterId,
                     history::Column::ChapterName,
@@ -25,49 +64,240 @@ pub async fn upsert_history(db: &DatabaseConnection, item: history::ActiveModel)
     Ok(())
 }
 
-pub async fn get_history(db: &DatabaseConnection) -> Result<Vec<history::Model>> {
-    let list = history::Entity::find()
+/// Lists history entries with the given visibility, where hidden ones make
+/// up the trash.
+pub async fn get_history(
+    db: &DatabaseConnection,
+    user_id: i32,
+    visible: bool,
+    keyword: Option<&str>,
+    page: u32,
+    page_size: u32,
+) -> Result<PageList<history::Model>> {
+    let mut query = history::Entity::find()
+        .filter(history::Column::UserId.eq(user_id))
+        .filter(history::Column::Visible.eq(visible));
+    if let Some(keyword) = keyword.map(str::trim).filter(|keyword| !keyword.is_empty()) {
+        query = query.filter(
+            Condition::any()
+                .add(history::Column::ComicName.contains(keyword))
+                .add(history::Column::ChapterName.contains(keyword)),
+        );
+    }
+
+    let paginator = query
         .order_by_desc(history::Column::UpdatedAt)
-        .all(db)
-        .await?;
-    Ok(list)
+        .paginate(db, page_size as u64);
+    let list = paginator.fetch_page(page.saturating_sub(1) as u64).await?;
+    let has_more = (page as u64) < paginator.num_pages().await?;
+
+    Ok(PageList {
+        list,
+        page,
+        has_more,
+    })
 }
 
 pub async fn get_comic_history(
     db: &DatabaseConnection,
+    user_id: i32,
     comic_id: &str,
 ) -> Result<Option<history::Model>> {
-    let item = history::Entity::find_by_id(comic_id)
-        .filter(history::Column::ComicId.eq(comic_id))
+    let item = history::Entity::find_by_id((user_id, comic_id.to_string()))
         .one(db)
         .await?;
     Ok(item)
 }
 
-pub async fn delete_history(db: &DatabaseConnection, comic_id: &str) -> Result<()> {
-    history::Entity::update(history::ActiveModel {
-        comic_id: Set(comic_id.to_string()),
-        visible: Set(false),
-        ..Default::default()
-    })
-    .filter(history::Column::ComicId.eq(comic_id))
-    .exec(db)
-    .await?;
+pub async fn delete_history(db: &DatabaseConnection, user_id: i32, comic_id: &str) -> Result<()> {
+    history::Entity::update_many()
+        .col_expr(history::Column::Visible, Expr::value(false))
+        .filter(history::Column::UserId.eq(user_id))
+        .filter(history::Column::ComicId.eq(comic_id))
+        .exec(db)
+        .await?;
     Ok(())
 }
 
-pub async fn get_library(db: &DatabaseConnection) -> Result<Vec<lib_comic::Model>> {
-    let list = lib_comic::Entity::find()
-        .order_by_desc(lib_comic::Column::CreatedAt)
+pub async fn restore_history(db: &DatabaseConnection, user_id: i32, comic_id: &str) -> Result<()> {
+    history::Entity::update_many()
+        .col_expr(history::Column::Visible, Expr::value(true))
+        .filter(history::Column::UserId.eq(user_id))
+        .filter(history::Column::ComicId.eq(comic_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Deletes the history of a comic for good, including its chapter progress.
+pub async fn purge_history(db: &DatabaseConnection, user_id: i32, comic_id: &str) -> Result<()> {
+    history::Entity::delete_by_id((user_id, comic_id.to_string()))
+        .exec(db)
+        .await?;
+    chapter_read::Entity::delete_many()
+        .filter(chapter_read::Column::UserId.eq(user_id))
+        .filter(chapter_read::Column::ComicId.eq(comic_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Deletes history last touched before `before`, along with the chapter
+/// progress of those comics, returning the number of purged history entries.
+pub async fn purge_history_before(
+    db: &DatabaseConnection,
+    user_id: i32,
+    before: DateTime<Utc>,
+) -> Result<u64> {
+    let txn = db.begin().await?;
+    let comic_ids = history::Entity::find()
+        .select_only()
+        .column(history::Column::ComicId)
+        .filter(history::Column::UserId.eq(user_id))
+        .filter(history::Column::UpdatedAt.lt(before))
+        .into_tuple::<String>()
+        .all(&txn)
+        .await?;
+    history::Entity::delete_many()
+        .filter(history::Column::UserId.eq(user_id))
+        .filter(history::Column::ComicId.is_in(comic_ids.clone()))
+        .exec(&txn)
+        .await?;
+    chapter_read::Entity::delete_many()
+        .filter(chapter_read::Column::UserId.eq(user_id))
+        .filter(chapter_read::Column::ComicId.is_in(comic_ids.clone()))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    Ok(comic_ids.len() as u64)
+}
+
+pub async fn upsert_chapter_read(
+    db: &DatabaseConnection,
+    item: chapter_read::ActiveModel,
+) -> Result<()> {
+    chapter_read::Entity::insert(item)
+        .on_conflict(
+            OnConflict::columns([
+                chapter_read::Column::UserId,
+                chapter_read::Column::ComicId,
+                chapter_read::Column::ChapterId,
+            ])
+            .update_columns([
+                chapter_read::Column::ChapterName,
+                chapter_read::Column::Page,
+                chapter_read::Column::TotalPages,
+                chapter_read::Column::ReadAt,
+                chapter_read::Column::Marked,
+            ])
+            // going back to an earlier page does not make a chapter unread
+            .value(
+                chapter_read::Column::Completed,
+                Expr::cust(r#""chapter_read"."completed" OR "excluded"."completed""#),
+            )
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_chapter_reads(
+    db: &impl ConnectionTrait,
+    user_id: i32,
+    comic_id: &str,
+) -> Result<Vec<chapter_read::Model>> {
+    let list = chapter_read::Entity::find()
+        .filter(chapter_read::Column::UserId.eq(user_id))
+        .filter(chapter_read::Column::ComicId.eq(comic_id))
+        .order_by_desc(chapter_read::Column::ReadAt)
         .all(db)
         .await?;
     Ok(list)
 }
 
+pub async fn get_chapter_read(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+    chapter_id: &str,
+) -> Result<Option<chapter_read::Model>> {
+    let item =
+        chapter_read::Entity::find_by_id((user_id, comic_id.to_string(), chapter_id.to_string()))
+            .one(db)
+            .await?;
+    Ok(item)
+}
+
+/// Marks chapters as completed, keeping the page progress and read time of
+/// the ones that have been opened before. New rows are flagged as `marked`,
+/// so they do not count as reading in the stats.
+pub async fn mark_chapters_read(
+    db: &DatabaseConnection,
+    items: Vec<chapter_read::ActiveModel>,
+) -> Result<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    chapter_read::Entity::insert_many(items)
+        .on_conflict(
+            OnConflict::columns([
+                chapter_read::Column::UserId,
+                chapter_read::Column::ComicId,
+                chapter_read::Column::ChapterId,
+            ])
+            .update_column(chapter_read::Column::Completed)
+            .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn mark_chapters_unread(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+    chapter_ids: Vec<String>,
+) -> Result<()> {
+    chapter_read::Entity::delete_many()
+        .filter(chapter_read::Column::UserId.eq(user_id))
+        .filter(chapter_read::Column::ComicId.eq(comic_id))
+        .filter(chapter_read::Column::ChapterId.is_in(chapter_ids))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Lists the library, or only the comics of a collection in their manual
+/// order.
+pub async fn get_library(
+    db: &DatabaseConnection,
+    user_id: i32,
+    collection_id: Option<i32>,
+) -> Result<Vec<lib_comic::Model>> {
+    let query = lib_comic::Entity::find().filter(lib_comic::Column::UserId.eq(user_id));
+    let query = match collection_id {
+        Some(collection_id) => query
+            .join(
+                sea_orm::JoinType::InnerJoin,
+                lib_comic::Relation::CollectionComic.def(),
+            )
+            .filter(collection_comic::Column::CollectionId.eq(collection_id))
+            .order_by_asc(collection_comic::Column::SortOrder),
+        None => query.order_by_desc(lib_comic::Column::CreatedAt),
+    };
+    let list = query.all(db).await?;
+    Ok(list)
+}
+
 pub async fn add_to_library(db: &DatabaseConnection, item: lib_comic::ActiveModel) -> Result<()> {
     lib_comic::Entity::insert(item)
         .on_conflict(
-            sea_query::OnConflict::column(lib_comic::Column::Id)
+            sea_query::OnConflict::columns([lib_comic::Column::UserId, lib_comic::Column::Id])
                 .do_nothing()
                 .to_owned(),
         )
@@ -77,12 +307,920 @@ pub async fn add_to_library(db: &DatabaseConnection, item: lib_comic::ActiveMode
     Ok(())
 }
 
-pub async fn remove_from_library(db: &DatabaseConnection, id: &str) -> Result<()> {
-    lib_comic::Entity::delete_by_id(id).exec(db).await?;
+pub async fn remove_from_library(db: &DatabaseConnection, user_id: i32, id: &str) -> Result<()> {
+    let txn = db.begin().await?;
+    collection_comic::Entity::delete_many()
+        .filter(collection_comic::Column::UserId.eq(user_id))
+        .filter(collection_comic::Column::ComicId.eq(id))
+        .exec(&txn)
+        .await?;
+    comic_note::Entity::delete_by_id((user_id, id.to_string()))
+        .exec(&txn)
+        .await?;
+    comic_tag::Entity::delete_many()
+        .filter(comic_tag::Column::UserId.eq(user_id))
+        .filter(comic_tag::Column::ComicId.eq(id))
+        .exec(&txn)
+        .await?;
+    lib_comic::Entity::delete_by_id((user_id, id.to_string()))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
     Ok(())
 }
 
-pub async fn check_in_library(db: &DatabaseConnection, id: &str) -> Result<bool> {
-    let in_library = lib_comic::Entity::find_by_id(id).one(db).await?;
+pub async fn check_in_library(db: &DatabaseConnection, user_id: i32, id: &str) -> Result<bool> {
+    let in_library = lib_comic::Entity::find_by_id((user_id, id.to_string()))
+        .one(db)
+        .await?;
     Ok(in_library.is_some())
 }
+
+pub async fn upsert_comic_meta(
+    db: &DatabaseConnection,
+    item: comic_meta::ActiveModel,
+) -> Result<()> {
+    comic_meta::Entity::insert(item)
+        .on_conflict(
+            OnConflict::columns([comic_meta::Column::Site, comic_meta::Column::Id])
+                .update_columns([
+                    comic_meta::Column::Name,
+                    comic_meta::Column::Cover,
+                    comic_meta::Column::Author,
+                    comic_meta::Column::Status,
+                    comic_meta::Column::Genres,
+                    comic_meta::Column::Region,
+                    comic_meta::Column::Aliases,
+                    comic_meta::Column::Rating,
+                    comic_meta::Column::LastChapterName,
+                    comic_meta::Column::LastUpdate,
+                    comic_meta::Column::ChapterCount,
+                    comic_meta::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Caches the metadata of a freshly fetched comic, used to list the library
+/// without hitting the sites.
+pub async fn cache_comic_meta(db: &DatabaseConnection, site: SiteId, comic: &Comic) -> Result<()> {
+    let item = comic_meta::ActiveModel {
+        site: Set(site.as_str().to_string()),
+        id: Set(comic.id.clone()),
+        name: Set(comic.name.clone()),
+        cover: Set(comic.cover.clone()),
+        author: Set(comic.author.clone().into()),
+        status: Set(comic.status.as_str().to_string()),
+        genres: Set(comic.genres.clone().into()),
+        region: Set(comic.region.clone()),
+        aliases: Set(comic.aliases.clone().into()),
+        rating: Set(comic.rating),
+        last_chapter_name: Set(comic.last_chapter_name.clone()),
+        last_update: Set(comic.last_update.clone()),
+        chapter_count: Set(comic
+            .chapter_groups
+            .iter()
+            .map(|group| group.chapters.len() as i32)
+            .sum()),
+        updated_at: Set(chrono::Utc::now()),
+        ..Default::default()
+    };
+    upsert_comic_meta(db, item).await
+}
+
+/// Deletes metadata last fetched before `before` of comics that no one has
+/// in their library or has read, which is what library listings and stats
+/// use it for. Comics are matched by id alone, since history and read events
+/// do not record the site.
+pub async fn delete_unused_comic_metas(
+    db: &DatabaseConnection,
+    before: DateTime<Utc>,
+) -> Result<u64> {
+    let res = comic_meta::Entity::delete_many()
+        .filter(comic_meta::Column::UpdatedAt.lt(before))
+        .filter(
+            comic_meta::Column::Id.not_in_subquery(
+                sea_query::Query::select()
+                    .column(lib_comic::Column::Id)
+                    .from(lib_comic::Entity)
+                    .to_owned(),
+            ),
+        )
+        .filter(
+            comic_meta::Column::Id.not_in_subquery(
+                sea_query::Query::select()
+                    .column(history::Column::ComicId)
+                    .from(history::Entity)
+                    .to_owned(),
+            ),
+        )
+        .filter(
+            comic_meta::Column::Id.not_in_subquery(
+                sea_query::Query::select()
+                    .column(read_event::Column::ComicId)
+                    .from(read_event::Entity)
+                    .to_owned(),
+            ),
+        )
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+pub async fn get_last_read_event(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Option<read_event::Model>> {
+    let item = read_event::Entity::find()
+        .filter(read_event::Column::UserId.eq(user_id))
+        .order_by_desc(read_event::Column::Id)
+        .one(db)
+        .await?;
+    Ok(item)
+}
+
+pub async fn add_read_event(db: &DatabaseConnection, item: read_event::ActiveModel) -> Result<()> {
+    read_event::Entity::insert(item).exec(db).await?;
+    Ok(())
+}
+
+pub async fn get_read_events(
+    db: &DatabaseConnection,
+    user_id: i32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<read_event::Model>> {
+    let list = read_event::Entity::find()
+        .filter(read_event::Column::UserId.eq(user_id))
+        .filter(read_event::Column::CreatedAt.gte(from))
+        .filter(read_event::Column::CreatedAt.lt(to))
+        .order_by_asc(read_event::Column::Id)
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn get_chapter_reads_between(
+    db: &DatabaseConnection,
+    user_id: i32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<chapter_read::Model>> {
+    let list = chapter_read::Entity::find()
+        .filter(chapter_read::Column::UserId.eq(user_id))
+        .filter(chapter_read::Column::ReadAt.gte(from))
+        .filter(chapter_read::Column::ReadAt.lt(to))
+        .filter(chapter_read::Column::Marked.eq(false))
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn get_histories(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_ids: Vec<String>,
+) -> Result<Vec<history::Model>> {
+    let list = history::Entity::find()
+        .filter(history::Column::UserId.eq(user_id))
+        .filter(history::Column::ComicId.is_in(comic_ids))
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+/// Counts the completed chapters of each comic.
+pub async fn get_completed_chapter_counts(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_ids: Vec<String>,
+) -> Result<Vec<(String, i64)>> {
+    let list = chapter_read::Entity::find()
+        .select_only()
+        .column(chapter_read::Column::ComicId)
+        .column_as(chapter_read::Column::ChapterId.count(), "count")
+        .filter(chapter_read::Column::UserId.eq(user_id))
+        .filter(chapter_read::Column::ComicId.is_in(comic_ids))
+        .filter(chapter_read::Column::Completed.eq(true))
+        .group_by(chapter_read::Column::ComicId)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn get_comic_metas(
+    db: &DatabaseConnection,
+    ids: Vec<String>,
+) -> Result<Vec<comic_meta::Model>> {
+    let list = comic_meta::Entity::find()
+        .filter(comic_meta::Column::Id.is_in(ids))
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+/// Lists collections in their manual order, along with their comic counts.
+pub async fn get_collections(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Vec<(collection::Model, i64)>> {
+    let list = collection::Entity::find()
+        .filter(collection::Column::UserId.eq(user_id))
+        .order_by_asc(collection::Column::SortOrder)
+        .all(db)
+        .await?;
+
+    let counts = collection_comic::Entity::find()
+        .select_only()
+        .column(collection_comic::Column::CollectionId)
+        .column_as(collection_comic::Column::ComicId.count(), "count")
+        .filter(collection_comic::Column::UserId.eq(user_id))
+        .group_by(collection_comic::Column::CollectionId)
+        .into_tuple::<(i32, i64)>()
+        .all(db)
+        .await?;
+
+    let list = list
+        .into_iter()
+        .map(|item| {
+            let count = counts
+                .iter()
+                .find(|(id, _)| *id == item.id)
+                .map_or(0, |(_, count)| *count);
+            (item, count)
+        })
+        .collect();
+    Ok(list)
+}
+
+pub async fn create_collection(
+    db: &DatabaseConnection,
+    user_id: i32,
+    name: &str,
+) -> Result<collection::Model> {
+    let max_order = collection::Entity::find()
+        .filter(collection::Column::UserId.eq(user_id))
+        .select_only()
+        .column_as(collection::Column::SortOrder.max(), "max")
+        .into_tuple::<Option<i32>>()
+        .one(db)
+        .await?
+        .flatten();
+
+    let now = chrono::Utc::now();
+    let item = collection::Entity::insert(collection::ActiveModel {
+        user_id: Set(user_id),
+        name: Set(name.to_string()),
+        sort_order: Set(max_order.map_or(0, |order| order + 1)),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    })
+    .exec_with_returning(db)
+    .await?;
+    Ok(item)
+}
+
+pub async fn rename_collection(
+    db: &DatabaseConnection,
+    user_id: i32,
+    id: i32,
+    name: &str,
+) -> Result<()> {
+    collection::Entity::update_many()
+        .col_expr(collection::Column::Name, Expr::value(name))
+        .col_expr(
+            collection::Column::UpdatedAt,
+            Expr::value(chrono::Utc::now()),
+        )
+        .filter(collection::Column::Id.eq(id))
+        .filter(collection::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_collection(db: &DatabaseConnection, user_id: i32, id: i32) -> Result<()> {
+    let txn = db.begin().await?;
+    collection_comic::Entity::delete_many()
+        .filter(collection_comic::Column::CollectionId.eq(id))
+        .filter(collection_comic::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    collection::Entity::delete_many()
+        .filter(collection::Column::Id.eq(id))
+        .filter(collection::Column::UserId.eq(user_id))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Saves the manual order of collections, following the order of `ids`.
+pub async fn reorder_collections(db: &DatabaseConnection, user_id: i32, ids: &[i32]) -> Result<()> {
+    let txn = db.begin().await?;
+    for (index, id) in ids.iter().enumerate() {
+        collection::Entity::update_many()
+            .col_expr(collection::Column::SortOrder, Expr::value(index as i32))
+            .filter(collection::Column::Id.eq(*id))
+            .filter(collection::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+pub async fn get_comic_collection_ids(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+) -> Result<Vec<i32>> {
+    let list = collection_comic::Entity::find()
+        .select_only()
+        .column(collection_comic::Column::CollectionId)
+        .filter(collection_comic::Column::UserId.eq(user_id))
+        .filter(collection_comic::Column::ComicId.eq(comic_id))
+        .into_tuple::<i32>()
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+/// Replaces the collections a comic belongs to. Collections it newly joins
+/// get it appended to the end of their manual order.
+pub async fn set_comic_collections(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+    collection_ids: &[i32],
+) -> Result<()> {
+    let txn = db.begin().await?;
+
+    // ignore collections of other users
+    let collection_ids = collection::Entity::find()
+        .select_only()
+        .column(collection::Column::Id)
+        .filter(collection::Column::UserId.eq(user_id))
+        .filter(collection::Column::Id.is_in(collection_ids.to_vec()))
+        .into_tuple::<i32>()
+        .all(&txn)
+        .await?;
+
+    collection_comic::Entity::delete_many()
+        .filter(collection_comic::Column::UserId.eq(user_id))
+        .filter(collection_comic::Column::ComicId.eq(comic_id))
+        .filter(collection_comic::Column::CollectionId.is_not_in(collection_ids.to_vec()))
+        .exec(&txn)
+        .await?;
+
+    let existing = collection_comic::Entity::find()
+        .select_only()
+        .column(collection_comic::Column::CollectionId)
+        .filter(collection_comic::Column::UserId.eq(user_id))
+        .filter(collection_comic::Column::ComicId.eq(comic_id))
+        .into_tuple::<i32>()
+        .all(&txn)
+        .await?;
+
+    for collection_id in collection_ids {
+        if existing.contains(&collection_id) {
+            continue;
+        }
+        let sort_order = next_collection_comic_order(&txn, collection_id).await?;
+        collection_comic::Entity::insert(collection_comic::ActiveModel {
+            collection_id: Set(collection_id),
+            user_id: Set(user_id),
+            comic_id: Set(comic_id.to_string()),
+            sort_order: Set(sort_order),
+            created_at: Set(chrono::Utc::now()),
+        })
+        .exec(&txn)
+        .await?;
+    }
+
+    txn.commit().await?;
+    Ok(())
+}
+
+pub async fn next_collection_comic_order(
+    db: &impl ConnectionTrait,
+    collection_id: i32,
+) -> Result<i32> {
+    let max_order = collection_comic::Entity::find()
+        .select_only()
+        .column_as(collection_comic::Column::SortOrder.max(), "max")
+        .filter(collection_comic::Column::CollectionId.eq(collection_id))
+        .into_tuple::<Option<i32>>()
+        .one(db)
+        .await?
+        .flatten();
+    Ok(max_order.map_or(0, |order| order + 1))
+}
+
+/// Saves the manual order of comics in a collection, following the order of
+/// `comic_ids`.
+pub async fn reorder_collection_comics(
+    db: &DatabaseConnection,
+    user_id: i32,
+    collection_id: i32,
+    comic_ids: &[String],
+) -> Result<()> {
+    let txn = db.begin().await?;
+    for (index, comic_id) in comic_ids.iter().enumerate() {
+        collection_comic::Entity::update_many()
+            .col_expr(
+                collection_comic::Column::SortOrder,
+                Expr::value(index as i32),
+            )
+            .filter(collection_comic::Column::CollectionId.eq(collection_id))
+            .filter(collection_comic::Column::UserId.eq(user_id))
+            .filter(collection_comic::Column::ComicId.eq(comic_id))
+            .exec(&txn)
+            .await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+pub async fn get_comic_note(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+) -> Result<Option<comic_note::Model>> {
+    let item = comic_note::Entity::find_by_id((user_id, comic_id.to_string()))
+        .one(db)
+        .await?;
+    Ok(item)
+}
+
+pub async fn get_comic_notes(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_ids: Vec<String>,
+) -> Result<Vec<comic_note::Model>> {
+    let list = comic_note::Entity::find()
+        .filter(comic_note::Column::UserId.eq(user_id))
+        .filter(comic_note::Column::ComicId.is_in(comic_ids))
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn upsert_comic_note(
+    db: &DatabaseConnection,
+    item: comic_note::ActiveModel,
+) -> Result<()> {
+    comic_note::Entity::insert(item)
+        .on_conflict(
+            OnConflict::columns([comic_note::Column::UserId, comic_note::Column::ComicId])
+                .update_columns([
+                    comic_note::Column::Rating,
+                    comic_note::Column::Notes,
+                    comic_note::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_comic_note(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+) -> Result<()> {
+    comic_note::Entity::delete_by_id((user_id, comic_id.to_string()))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_comic_tags(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_ids: Vec<String>,
+) -> Result<Vec<comic_tag::Model>> {
+    let list = comic_tag::Entity::find()
+        .filter(comic_tag::Column::UserId.eq(user_id))
+        .filter(comic_tag::Column::ComicId.is_in(comic_ids))
+        .order_by_asc(comic_tag::Column::Tag)
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn set_comic_tags(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+    tags: &[String],
+) -> Result<()> {
+    let txn = db.begin().await?;
+
+    comic_tag::Entity::delete_many()
+        .filter(comic_tag::Column::UserId.eq(user_id))
+        .filter(comic_tag::Column::ComicId.eq(comic_id))
+        .exec(&txn)
+        .await?;
+
+    if !tags.is_empty() {
+        let now = chrono::Utc::now();
+        comic_tag::Entity::insert_many(tags.iter().map(|tag| comic_tag::ActiveModel {
+            user_id: Set(user_id),
+            comic_id: Set(comic_id.to_string()),
+            tag: Set(tag.clone()),
+            created_at: Set(now),
+        }))
+        .exec(&txn)
+        .await?;
+    }
+
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Lists every tag in use along with the number of comics tagged with it.
+pub async fn get_all_tags(db: &DatabaseConnection, user_id: i32) -> Result<Vec<(String, i64)>> {
+    let list = comic_tag::Entity::find()
+        .filter(comic_tag::Column::UserId.eq(user_id))
+        .select_only()
+        .column(comic_tag::Column::Tag)
+        .column_as(comic_tag::Column::ComicId.count(), "count")
+        .group_by(comic_tag::Column::Tag)
+        .order_by_asc(comic_tag::Column::Tag)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn get_user(db: &DatabaseConnection, id: i32) -> Result<Option<user::Model>> {
+    let item = user::Entity::find_by_id(id).one(db).await?;
+    Ok(item)
+}
+
+pub async fn get_user_by_username(
+    db: &DatabaseConnection,
+    username: &str,
+) -> Result<Option<user::Model>> {
+    let item = user::Entity::find()
+        .filter(user::Column::Username.eq(username))
+        .one(db)
+        .await?;
+    Ok(item)
+}
+
+pub async fn get_users(db: &DatabaseConnection) -> Result<Vec<user::Model>> {
+    let list = user::Entity::find()
+        .order_by_asc(user::Column::Id)
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn get_users_without_password(db: &DatabaseConnection) -> Result<Vec<user::Model>> {
+    let list = user::Entity::find()
+        .filter(user::Column::PasswordHash.eq(""))
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn create_user(
+    db: &DatabaseConnection,
+    username: &str,
+    password_hash: &str,
+    role: &str,
+) -> Result<user::Model> {
+    let now = chrono::Utc::now();
+    let item = user::Entity::insert(user::ActiveModel {
+        username: Set(username.to_string()),
+        password_hash: Set(password_hash.to_string()),
+        role: Set(role.to_string()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        ..Default::default()
+    })
+    .exec_with_returning(db)
+    .await?;
+    Ok(item)
+}
+
+pub async fn set_user_password(
+    db: &DatabaseConnection,
+    id: i32,
+    password_hash: &str,
+) -> Result<()> {
+    user::Entity::update_many()
+        .col_expr(user::Column::PasswordHash, Expr::value(password_hash))
+        .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+        .filter(user::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Deletes a user along with everything scoped to them.
+pub async fn delete_user(db: &DatabaseConnection, id: i32) -> Result<()> {
+    user::Entity::delete_by_id(id).exec(db).await?;
+    Ok(())
+}
+
+pub async fn get_setting(db: &DatabaseConnection, key: &str) -> Result<Option<String>> {
+    let item = setting::Entity::find_by_id(key).one(db).await?;
+    Ok(item.map(|item| item.value))
+}
+
+pub async fn set_setting(db: &DatabaseConnection, key: &str, value: &str) -> Result<()> {
+    setting::Entity::insert(setting::ActiveModel {
+        key: Set(key.to_string()),
+        value: Set(value.to_string()),
+    })
+    .on_conflict(
+        OnConflict::column(setting::Column::Key)
+            .update_column(setting::Column::Value)
+            .to_owned(),
+    )
+    .exec(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn create_session(
+    db: &DatabaseConnection,
+    item: session::ActiveModel,
+) -> Result<session::Model> {
+    let item = session::Entity::insert(item)
+        .exec_with_returning(db)
+        .await?;
+    Ok(item)
+}
+
+/// Expired sessions are treated as missing.
+pub async fn get_session_by_token_hash(
+    db: &DatabaseConnection,
+    token_hash: &str,
+) -> Result<Option<(session::Model, user::Model)>> {
+    let item = session::Entity::find()
+        .filter(session::Column::TokenHash.eq(token_hash))
+        .filter(session::Column::ExpiresAt.gt(chrono::Utc::now()))
+        .find_also_related(user::Entity)
+        .one(db)
+        .await?;
+    Ok(item.and_then(|(session, user)| Some((session, user?))))
+}
+
+pub async fn touch_session(db: &DatabaseConnection, id: i32) -> Result<()> {
+    session::Entity::update_many()
+        .col_expr(session::Column::LastSeenAt, Expr::value(chrono::Utc::now()))
+        .filter(session::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_user_sessions(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Vec<session::Model>> {
+    let list = session::Entity::find()
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::ExpiresAt.gt(chrono::Utc::now()))
+        .order_by_desc(session::Column::LastSeenAt)
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+/// Returns whether the session existed.
+pub async fn delete_session(db: &DatabaseConnection, user_id: i32, id: i32) -> Result<bool> {
+    let res = session::Entity::delete_many()
+        .filter(session::Column::UserId.eq(user_id))
+        .filter(session::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+pub async fn delete_session_by_token_hash(db: &DatabaseConnection, token_hash: &str) -> Result<()> {
+    session::Entity::delete_many()
+        .filter(session::Column::TokenHash.eq(token_hash))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Logs a user out everywhere, e.g. after their password is reset, except
+/// on the session given in `keep`.
+pub async fn delete_user_sessions(
+    db: &DatabaseConnection,
+    user_id: i32,
+    keep: Option<i32>,
+) -> Result<()> {
+    let mut query = session::Entity::delete_many().filter(session::Column::UserId.eq(user_id));
+    if let Some(keep) = keep {
+        query = query.filter(session::Column::Id.ne(keep));
+    }
+    query.exec(db).await?;
+    Ok(())
+}
+
+pub async fn delete_expired_sessions(db: &DatabaseConnection) -> Result<u64> {
+    let res = session::Entity::delete_many()
+        .filter(session::Column::ExpiresAt.lte(chrono::Utc::now()))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+pub async fn add_login_attempt(
+    db: &DatabaseConnection,
+    item: login_attempt::ActiveModel,
+) -> Result<()> {
+    login_attempt::Entity::insert(item).exec(db).await?;
+    Ok(())
+}
+
+/// Attempts matching `value` on `column`, newest first, leaving out the ones
+/// rejected by the lockout itself.
+pub async fn get_login_attempts_since(
+    db: &DatabaseConnection,
+    column: login_attempt::Column,
+    value: &str,
+    since: DateTime<Utc>,
+) -> Result<Vec<login_attempt::Model>> {
+    let list = login_attempt::Entity::find()
+        .filter(column.eq(value))
+        .filter(login_attempt::Column::CreatedAt.gt(since))
+        .filter(login_attempt::Column::Result.ne(LoginResult::Locked.as_str()))
+        .order_by_desc(login_attempt::Column::CreatedAt)
+        .limit(100)
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+pub async fn get_login_attempts(
+    db: &DatabaseConnection,
+    username: Option<&str>,
+    ip: Option<&str>,
+    result: Option<LoginResult>,
+    page: u32,
+    page_size: u32,
+) -> Result<PageList<login_attempt::Model>> {
+    let mut query = login_attempt::Entity::find();
+    if let Some(username) = username {
+        query = query.filter(login_attempt::Column::Username.eq(username));
+    }
+    if let Some(ip) = ip {
+        query = query.filter(login_attempt::Column::Ip.eq(ip));
+    }
+    if let Some(result) = result {
+        query = query.filter(login_attempt::Column::Result.eq(result.as_str()));
+    }
+
+    let paginator = query
+        .order_by_desc(login_attempt::Column::CreatedAt)
+        .paginate(db, page_size as u64);
+    let list = paginator.fetch_page(page.saturating_sub(1) as u64).await?;
+    let has_more = (page as u64) < paginator.num_pages().await?;
+
+    Ok(PageList {
+        list,
+        page,
+        has_more,
+    })
+}
+
+pub async fn delete_login_attempts_before(
+    db: &DatabaseConnection,
+    before: DateTime<Utc>,
+) -> Result<u64> {
+    let res = login_attempt::Entity::delete_many()
+        .filter(login_attempt::Column::CreatedAt.lt(before))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
+
+pub async fn create_api_token(
+    db: &DatabaseConnection,
+    item: api_token::ActiveModel,
+) -> Result<api_token::Model> {
+    let item = api_token::Entity::insert(item)
+        .exec_with_returning(db)
+        .await?;
+    Ok(item)
+}
+
+/// Expired tokens are treated as missing.
+pub async fn get_api_token_by_hash(
+    db: &DatabaseConnection,
+    token_hash: &str,
+) -> Result<Option<(api_token::Model, user::Model)>> {
+    let item = api_token::Entity::find()
+        .filter(api_token::Column::TokenHash.eq(token_hash))
+        .filter(
+            Condition::any()
+                .add(api_token::Column::ExpiresAt.is_null())
+                .add(api_token::Column::ExpiresAt.gt(chrono::Utc::now())),
+        )
+        .find_also_related(user::Entity)
+        .one(db)
+        .await?;
+    Ok(item.and_then(|(token, user)| Some((token, user?))))
+}
+
+pub async fn touch_api_token(db: &DatabaseConnection, id: i32) -> Result<()> {
+    api_token::Entity::update_many()
+        .col_expr(
+            api_token::Column::LastUsedAt,
+            Expr::value(chrono::Utc::now()),
+        )
+        .filter(api_token::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_api_tokens(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Vec<api_token::Model>> {
+    let list = api_token::Entity::find()
+        .filter(api_token::Column::UserId.eq(user_id))
+        .order_by_desc(api_token::Column::CreatedAt)
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+/// Returns whether the token existed.
+pub async fn delete_api_token(db: &DatabaseConnection, user_id: i32, id: i32) -> Result<bool> {
+    let res = api_token::Entity::delete_many()
+        .filter(api_token::Column::UserId.eq(user_id))
+        .filter(api_token::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+pub async fn create_share_link(
+    db: &DatabaseConnection,
+    item: share_link::ActiveModel,
+) -> Result<share_link::Model> {
+    let item = share_link::Entity::insert(item)
+        .exec_with_returning(db)
+        .await?;
+    Ok(item)
+}
+
+/// Expired links are treated as missing.
+pub async fn get_share_link_by_hash(
+    db: &DatabaseConnection,
+    token_hash: &str,
+) -> Result<Option<share_link::Model>> {
+    let item = share_link::Entity::find()
+        .filter(share_link::Column::TokenHash.eq(token_hash))
+        .filter(share_link::Column::ExpiresAt.gt(chrono::Utc::now()))
+        .one(db)
+        .await?;
+    Ok(item)
+}
+
+pub async fn get_share_links(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Vec<share_link::Model>> {
+    let list = share_link::Entity::find()
+        .filter(share_link::Column::UserId.eq(user_id))
+        .order_by_desc(share_link::Column::CreatedAt)
+        .all(db)
+        .await?;
+    Ok(list)
+}
+
+/// Returns whether the link existed.
+pub async fn delete_share_link(db: &DatabaseConnection, user_id: i32, id: i32) -> Result<bool> {
+    let res = share_link::Entity::delete_many()
+        .filter(share_link::Column::UserId.eq(user_id))
+        .filter(share_link::Column::Id.eq(id))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected > 0)
+}
+
+pub async fn delete_expired_share_links(db: &DatabaseConnection) -> Result<u64> {
+    let res = share_link::Entity::delete_many()
+        .filter(share_link::Column::ExpiresAt.lte(chrono::Utc::now()))
+        .exec(db)
+        .await?;
+    Ok(res.rows_affected)
+}
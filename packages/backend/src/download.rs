@@ -0,0 +1,142 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, Result};
+use reqwest::header;
+use serde::Serialize;
+use tokio::fs;
+
+use crate::{
+    chapter::{find_chapter, ChapterNumber},
+    site::{http_client, ComicChapterBrief, SiteId},
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadedChapter {
+    pub id: String,
+    pub name: String,
+    pub dir: PathBuf,
+    pub images: usize,
+    /// Images that were already on disk.
+    pub skipped: usize,
+}
+
+/// Keeps names of comics and chapters usable as directory names, falling
+/// back to the id when nothing of the name is left.
+fn sanitize(name: &str, id: &str) -> String {
+    let clean = |name: &str| {
+        let name = name
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect::<String>();
+        name.trim().trim_matches('.').to_string()
+    };
+    match clean(name) {
+        name if name.is_empty() => clean(id),
+        name => name,
+    }
+}
+
+/// Chapters are named by their normalized number, e.g. `Ch.012.5`, so they
+/// sort the same everywhere. The name from the site is used for chapters
+/// without a number, and for repeated numbers so they do not share a
+/// directory.
+fn chapter_dir_name(chapter: &ComicChapterBrief, used: &mut HashSet<String>) -> String {
+    let label = ChapterNumber {
+        kind: chapter.kind,
+        number: chapter.number,
+    }
+    .export_label();
+    match label {
+        Some(label) if used.insert(label.clone()) => label,
+        _ => sanitize(&chapter.name, &chapter.id),
+    }
+}
+
+/// `https://host/001.jpg.webp?e=1&m=2` -> `webp`
+fn image_extension(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .filter(|ext| !ext.is_empty() && ext.len() <= 5 && !ext.contains('/'))
+        .unwrap_or("jpg")
+}
+
+/// Downloads chapters of a comic into `out/<comic>/<chapter>/`, every chapter
+/// when `chapter_ids` is empty. Images already on disk are skipped, so an
+/// interrupted download can be resumed.
+pub async fn download_chapters(
+    site: SiteId,
+    comic_id: &str,
+    chapter_ids: &[String],
+    out: &Path,
+) -> Result<Vec<DownloadedChapter>> {
+    let comic = site.get_comic(comic_id.to_string()).await?;
+    let chapters = if chapter_ids.is_empty() {
+        comic
+            .chapter_groups
+            .iter()
+            .flat_map(|group| &group.chapters)
+            .collect::<Vec<_>>()
+    } else {
+        chapter_ids
+            .iter()
+            .map(|chapter_id| {
+                find_chapter(&comic.chapter_groups, chapter_id)
+                    .ok_or_else(|| anyhow!("Chapter {chapter_id} not found"))
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    let comic_dir = out.join(sanitize(&comic.name, &comic.id));
+    let mut downloaded = vec![];
+    let mut used = HashSet::new();
+    for chapter in chapters {
+        let dir = comic_dir.join(chapter_dir_name(chapter, &mut used));
+        fs::create_dir_all(&dir).await?;
+
+        let images = site
+            .get_chapter(comic.id.clone(), chapter.id.clone())
+            .await?
+            .images;
+        let mut skipped = 0;
+        for (index, url) in images.iter().enumerate() {
+            let path = dir.join(format!("{:03}.{}", index + 1, image_extension(url)));
+            if fs::try_exists(&path).await? {
+                skipped += 1;
+                continue;
+            }
+
+            let bytes = http_client()
+                .get(url)
+                .header(header::REFERER, site.referer())
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?;
+            // written aside first, so a partial image is never taken as done
+            let part = path.with_extension("part");
+            fs::write(&part, &bytes).await?;
+            fs::rename(&part, &path).await?;
+        }
+
+        tracing::info!("downloaded chapter {} to {}", chapter.name, dir.display());
+        downloaded.push(DownloadedChapter {
+            id: chapter.id.clone(),
+            name: chapter.name.clone(),
+            dir,
+            images: images.len(),
+            skipped,
+        });
+    }
+
+    Ok(downloaded)
+}
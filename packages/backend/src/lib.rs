@@ -1,3 +1,17 @@
+pub mod auth;
+pub mod backup;
+pub mod chapter;
+pub mod check;
+pub mod config;
 pub mod db;
+pub mod download;
+pub mod library;
+pub mod migrate;
+pub mod scheduler;
+pub mod search;
 pub mod server;
+pub mod shutdown;
 pub mod site;
+pub mod stats;
+pub mod tachiyomi;
+pub mod throttle;
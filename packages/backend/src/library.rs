@@ -0,0 +1,218 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use anyhow::Result;
+use entity::{history, lib_comic};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db,
+    site::{ComicStatus, PageList, SiteId},
+};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LibrarySort {
+    Name,
+    AddedAt,
+    LastRead,
+    LastUpdate,
+    UnreadCount,
+    Rating,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryFilter {
+    /// Defaults to the manual order inside a collection, and to the date
+    /// added otherwise.
+    pub sort: Option<LibrarySort>,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub status: Option<ComicStatus>,
+    pub site: Option<SiteId>,
+    pub collection_id: Option<i32>,
+    pub has_unread: Option<bool>,
+    pub tag: Option<String>,
+    /// Personal rating, from 1 to 10.
+    pub min_rating: Option<i32>,
+    /// Matched against the name, notes and tags of comics.
+    pub keyword: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryItem {
+    #[serde(flatten)]
+    pub comic: lib_comic::Model,
+    pub status: ComicStatus,
+    pub last_chapter_name: String,
+    pub last_update: String,
+    /// Chapters known from the last time the comic was opened.
+    pub chapter_count: i64,
+    pub read_count: i64,
+    pub unread_count: i64,
+    pub history: Option<history::Model>,
+    pub rating: Option<i32>,
+    pub notes: String,
+    pub tags: Vec<String>,
+}
+
+/// Lists the library along with the cached metadata and reading progress of
+/// every comic, so clients do not need to look them up one by one.
+pub async fn list_library(
+    db: &DatabaseConnection,
+    user_id: i32,
+    filter: &LibraryFilter,
+    page: u32,
+    page_size: u32,
+) -> Result<PageList<LibraryItem>> {
+    let comics = db::get_library(db, user_id, filter.collection_id).await?;
+    let ids = comics
+        .iter()
+        .map(|comic| comic.id.clone())
+        .collect::<Vec<_>>();
+
+    let mut metas = db::get_comic_metas(db, ids.clone())
+        .await?
+        .into_iter()
+        .map(|meta| ((meta.site.clone(), meta.id.clone()), meta))
+        .collect::<HashMap<_, _>>();
+    let mut histories = db::get_histories(db, user_id, ids.clone())
+        .await?
+        .into_iter()
+        .map(|item| (item.comic_id.clone(), item))
+        .collect::<HashMap<_, _>>();
+    let read_counts = db::get_completed_chapter_counts(db, user_id, ids.clone())
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let mut notes = db::get_comic_notes(db, user_id, ids.clone())
+        .await?
+        .into_iter()
+        .map(|note| (note.comic_id.clone(), note))
+        .collect::<HashMap<_, _>>();
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for item in db::get_comic_tags(db, user_id, ids).await? {
+        tags.entry(item.comic_id).or_default().push(item.tag);
+    }
+    let keyword = filter
+        .keyword
+        .as_deref()
+        .map(|keyword| keyword.trim().to_lowercase())
+        .filter(|keyword| !keyword.is_empty());
+
+    let mut list = comics
+        .into_iter()
+        .map(|comic| {
+            let meta = metas.remove(&(comic.site.clone(), comic.id.clone()));
+            let chapter_count = meta.as_ref().map_or(0, |meta| meta.chapter_count as i64);
+            let read_count = read_counts.get(&comic.id).copied().unwrap_or(0);
+            let note = notes.remove(&comic.id);
+
+            LibraryItem {
+                status: meta
+                    .as_ref()
+                    .map_or(ComicStatus::Unknown, |meta| meta.status.as_str().into()),
+                last_chapter_name: meta
+                    .as_ref()
+                    .map(|meta| meta.last_chapter_name.clone())
+                    .unwrap_or_default(),
+                last_update: meta
+                    .as_ref()
+                    .map(|meta| meta.last_update.clone())
+                    .unwrap_or_default(),
+                chapter_count,
+                read_count,
+                unread_count: (chapter_count - read_count).max(0),
+                history: histories.remove(&comic.id),
+                rating: note.as_ref().and_then(|note| note.rating),
+                notes: note.map(|note| note.notes).unwrap_or_default(),
+                tags: tags.remove(&comic.id).unwrap_or_default(),
+                comic,
+            }
+        })
+        .filter(|item| filter.status.is_none_or(|status| item.status == status))
+        .filter(|item| {
+            filter
+                .site
+                .is_none_or(|site| item.comic.site == site.as_str())
+        })
+        .filter(|item| {
+            filter
+                .has_unread
+                .is_none_or(|has_unread| (item.unread_count > 0) == has_unread)
+        })
+        .filter(|item| {
+            filter
+                .tag
+                .as_ref()
+                .is_none_or(|tag| item.tags.contains(tag))
+        })
+        .filter(|item| {
+            filter
+                .min_rating
+                .is_none_or(|min_rating| item.rating.is_some_and(|rating| rating >= min_rating))
+        })
+        .filter(|item| {
+            keyword.as_ref().is_none_or(|keyword| {
+                item.comic.name.to_lowercase().contains(keyword)
+                    || item.notes.to_lowercase().contains(keyword)
+                    || item
+                        .tags
+                        .iter()
+                        .any(|tag| tag.to_lowercase().contains(keyword))
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(sort) = filter.sort {
+        list.sort_by(|a, b| {
+            let ordering = compare_items(a, b, sort);
+            match filter.order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        });
+    }
+
+    let total = list.len();
+    let start = (page.max(1) as usize - 1) * page_size as usize;
+    let list = list
+        .into_iter()
+        .skip(start)
+        .take(page_size as usize)
+        .collect();
+
+    Ok(PageList {
+        list,
+        page,
+        has_more: start + (page_size as usize) < total,
+    })
+}
+
+fn compare_items(a: &LibraryItem, b: &LibraryItem, sort: LibrarySort) -> Ordering {
+    match sort {
+        LibrarySort::Name => a.comic.name.cmp(&b.comic.name),
+        LibrarySort::AddedAt => a.comic.created_at.cmp(&b.comic.created_at),
+        // never read comics sort as the oldest
+        LibrarySort::LastRead => a
+            .history
+            .as_ref()
+            .map(|item| item.updated_at)
+            .cmp(&b.history.as_ref().map(|item| item.updated_at)),
+        // `YYYY-MM-DD`, where unknown dates are empty and sort first
+        LibrarySort::LastUpdate => a.last_update.cmp(&b.last_update),
+        LibrarySort::UnreadCount => a.unread_count.cmp(&b.unread_count),
+        // unrated comics sort as the lowest
+        LibrarySort::Rating => a.rating.cmp(&b.rating),
+    }
+}
@@ -1,7 +1,161 @@
-use anyhow::Result;
-use backend::server::run;
+use std::{
+    fs,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, bail, Result};
+use backend::{
+    auth::{self, Role, DEFAULT_ADMIN},
+    backup::{export_backup, import_backup, MergeStrategy},
+    check::check_site,
+    config::{Config, ConfigArgs},
+    db,
+    download::download_chapters,
+    server::run,
+    site::{http_client, init_http_client, SiteId},
+    tachiyomi::{decode_backup, import_tachiyomi_backup},
+};
+use chrono::{Days, Utc};
+use clap::{Parser, Subcommand};
+use sea_orm::{Database, DatabaseConnection};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+#[derive(Parser)]
+#[command(version, about = "Self-hosted comic site")]
+struct Cli {
+    #[command(flatten)]
+    config: ConfigArgs,
+    /// Serves the site when missing
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the API and the web app
+    Serve,
+    /// Apply pending database migrations
+    Migrate,
+    /// Manage accounts
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Export the library, collections, notes and history of a user
+    Export {
+        file: PathBuf,
+        #[arg(long, default_value = DEFAULT_ADMIN)]
+        user: String,
+    },
+    /// Import a backup made by `export`
+    Import {
+        file: PathBuf,
+        /// How to treat items that already exist: skip, overwrite or newest-wins
+        #[arg(default_value = "newest-wins", value_parser = parse_merge_strategy)]
+        strategy: MergeStrategy,
+        #[arg(long, default_value = DEFAULT_ADMIN)]
+        user: String,
+    },
+    /// Import a Tachiyomi or Mihon backup
+    ImportTachiyomi {
+        file: PathBuf,
+        #[arg(long, default_value = DEFAULT_ADMIN)]
+        user: String,
+    },
+    /// Download the images of a comic's chapters
+    Download {
+        comic: String,
+        /// Chapter ids, every chapter when none are given
+        chapters: Vec<String>,
+        #[arg(long, default_value = "manhuagui", value_parser = parse_site)]
+        site: SiteId,
+        #[arg(long, default_value = "data/downloads")]
+        out: PathBuf,
+    },
+    /// Manage cached comic metadata
+    #[command(subcommand)]
+    Cache(CacheCommand),
+    /// Search, open a comic and read a chapter on every enabled site, to
+    /// catch changes to their pages
+    CheckSites,
+    /// Exit with an error unless the running server is ready, for container
+    /// health checks
+    Healthcheck,
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+    /// Create an account
+    Add {
+        username: String,
+        #[arg(long, value_enum, default_value_t = Role::Member)]
+        role: Role,
+        /// A random one is generated and printed when missing
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Set a new password and log out every session of an account
+    ResetPassword {
+        username: String,
+        /// A random one is generated and printed when missing
+        #[arg(long)]
+        password: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Delete metadata of comics no one has in their library or has read
+    Prune {
+        /// Only metadata not refreshed for this many days
+        #[arg(long, default_value_t = 30)]
+        older_than_days: u64,
+    },
+}
+
+/// Where the server configured by `bind` can be reached from this machine.
+fn local_addr(bind: SocketAddr) -> SocketAddr {
+    match bind {
+        SocketAddr::V4(addr) if addr.ip().is_unspecified() => {
+            SocketAddr::from((Ipv4Addr::LOCALHOST, addr.port()))
+        }
+        SocketAddr::V6(addr) if addr.ip().is_unspecified() => {
+            SocketAddr::from((Ipv6Addr::LOCALHOST, addr.port()))
+        }
+        addr => addr,
+    }
+}
+
+fn parse_merge_strategy(value: &str) -> Result<MergeStrategy> {
+    MergeStrategy::parse(value).ok_or_else(|| anyhow!("Unknown merge strategy {value}"))
+}
+
+fn parse_site(value: &str) -> Result<SiteId> {
+    SiteId::parse(value).ok_or_else(|| anyhow!("Unknown site {value}"))
+}
+
+/// Backups are per user, the admin's unless `--user <name>` is given.
+async fn find_user(db: &DatabaseConnection, username: &str) -> Result<i32> {
+    let user = db::get_user_by_username(db, username)
+        .await?
+        .ok_or_else(|| anyhow!("User {username} does not exist"))?;
+    Ok(user.id)
+}
+
+/// Prints generated passwords, since they are not shown anywhere else.
+fn password_or_generate(password: Option<String>) -> Result<String> {
+    match password {
+        Some(password) => {
+            auth::validate_password(&password)?;
+            Ok(password)
+        }
+        None => {
+            let password = auth::new_password();
+            println!("Password: {password}");
+            Ok(password)
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::registry()
@@ -19,7 +173,123 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    run().await?;
+    let cli = Cli::parse();
+    let config = Config::load(cli.config)?;
+    init_http_client(&config.http)?;
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => run(config).await?,
+        Command::Migrate => {
+            let db = Database::connect(&config.database_url).await?;
+            let applied = db::migrate(&db).await?;
+            if applied.is_empty() {
+                println!("Database is up to date");
+            }
+            for name in applied {
+                println!("Applied {name}");
+            }
+        }
+        Command::User(UserCommand::Add {
+            username,
+            role,
+            password,
+        }) => {
+            let db = db::connect(&config.database_url).await?;
+            let username = username.trim();
+            if username.is_empty() {
+                bail!("Username cannot be empty");
+            }
+            if db::get_user_by_username(&db, username).await?.is_some() {
+                bail!("User {username} already exists");
+            }
+            let password = password_or_generate(password)?;
+            db::create_user(
+                &db,
+                username,
+                &auth::hash_password(&password)?,
+                role.as_str(),
+            )
+            .await?;
+            println!("Created {} {username}", role.as_str());
+        }
+        Command::User(UserCommand::ResetPassword { username, password }) => {
+            let db = db::connect(&config.database_url).await?;
+            let user_id = find_user(&db, &username).await?;
+            let password = password_or_generate(password)?;
+            db::set_user_password(&db, user_id, &auth::hash_password(&password)?).await?;
+            db::delete_user_sessions(&db, user_id, None).await?;
+            println!("Reset the password of {username}");
+        }
+        Command::Export { file, user } => {
+            let db = db::connect(&config.database_url).await?;
+            let backup = export_backup(&db, find_user(&db, &user).await?).await?;
+            fs::write(&file, serde_json::to_string_pretty(&backup)?)?;
+            println!(
+                "Exported {} comics to {}",
+                backup.library.len(),
+                file.display()
+            );
+        }
+        Command::Import {
+            file,
+            strategy,
+            user,
+        } => {
+            let backup = serde_json::from_str(&fs::read_to_string(file)?)?;
+            let db = db::connect(&config.database_url).await?;
+            let user_id = find_user(&db, &user).await?;
+            let summary = import_backup(&db, user_id, backup, strategy).await?;
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        Command::ImportTachiyomi { file, user } => {
+            let bytes = fs::read(file)?;
+            let db = db::connect(&config.database_url).await?;
+            let user_id = find_user(&db, &user).await?;
+            let summary = import_tachiyomi_backup(&db, user_id, decode_backup(&bytes)?).await?;
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        Command::Download {
+            comic,
+            chapters,
+            site,
+            out,
+        } => {
+            if !config.enabled_sites().contains(&site) {
+                bail!("Site {} is not enabled", site.as_str());
+            }
+            let downloaded = download_chapters(site, &comic, &chapters, &out).await?;
+            println!("{}", serde_json::to_string_pretty(&downloaded)?);
+        }
+        Command::Cache(CacheCommand::Prune { older_than_days }) => {
+            let db = db::connect(&config.database_url).await?;
+            let before = Utc::now()
+                .checked_sub_days(Days::new(older_than_days))
+                .ok_or_else(|| anyhow!("--older-than-days {older_than_days} is out of range"))?;
+            let count = db::delete_unused_comic_metas(&db, before).await?;
+            println!("Deleted metadata of {count} comics");
+        }
+        Command::CheckSites => {
+            let mut failed = false;
+            for site in config.enabled_sites() {
+                let status = check_site(site).await;
+                match &status.error {
+                    None => println!("{}: ok in {}ms", site.as_str(), status.elapsed_ms),
+                    Some(err) => println!("{}: failed, {err}", site.as_str()),
+                }
+                failed |= !status.ok;
+            }
+            if failed {
+                bail!("Some sites failed the check");
+            }
+        }
+        Command::Healthcheck => {
+            let url = format!("http://{}/readyz", local_addr(config.bind));
+            let res = http_client().get(&url).send().await?;
+            if !res.status().is_success() {
+                bail!("{url} returned {}: {}", res.status(), res.text().await?);
+            }
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use entity::{chapter_read, collection_comic, comic_note, comic_tag, history, lib_comic};
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
+    Set, TransactionTrait,
+};
+use serde::Serialize;
+
+use crate::{
+    chapter::{parse_chapter_number, ChapterKind},
+    db,
+    search::{normalize_title, search_all, SiteSearchStatus, SourcedComic, DEFAULT_SITE_TIMEOUT},
+    site::{ComicChapterBrief, SiteId},
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationCandidates {
+    /// Comics whose normalized title matches come first.
+    pub list: Vec<SourcedComic>,
+    pub sites: Vec<SiteSearchStatus>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationResult {
+    pub comic: lib_comic::Model,
+    pub mapped_chapters: usize,
+    /// Names of read chapters without an equivalent on the target site.
+    pub unmapped_chapters: Vec<String>,
+}
+
+/// Searches `sites` for the title of a library comic, leaving out the comic
+/// itself.
+pub async fn find_candidates(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+    sites: &[SiteId],
+) -> Result<MigrationCandidates> {
+    let comic = lib_comic::Entity::find_by_id((user_id, comic_id.to_string()))
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow!("Comic {comic_id} is not in the library"))?;
+
+    let res = search_all(sites, &comic.name, DEFAULT_SITE_TIMEOUT).await;
+    let title = normalize_title(&comic.name);
+
+    let mut list = res
+        .list
+        .into_iter()
+        .flat_map(|group| group.sources)
+        .filter(|item| !(item.site.as_str() == comic.site && item.comic.id == comic.id))
+        .collect::<Vec<_>>();
+    // stable, so each site keeps its own relevance order
+    list.sort_by_key(|item| normalize_title(&item.comic.name) != title);
+
+    Ok(MigrationCandidates {
+        list,
+        sites: res.sites,
+    })
+}
+
+/// Chapters are matched by kind and number, since ids and names differ
+/// between sites.
+fn chapter_key(kind: ChapterKind, number: Option<f64>) -> Option<(ChapterKind, u64)> {
+    number.map(|number| (kind, number.to_bits()))
+}
+
+/// Moves a library comic to its equivalent on another site, carrying over
+/// its collections, notes, tags, history and the read state of chapters
+/// found on both sites.
+pub async fn migrate_comic(
+    db: &DatabaseConnection,
+    user_id: i32,
+    comic_id: &str,
+    target_site: SiteId,
+    target_id: &str,
+) -> Result<MigrationResult> {
+    let source = lib_comic::Entity::find_by_id((user_id, comic_id.to_string()))
+        .one(db)
+        .await?
+        .ok_or_else(|| anyhow!("Comic {comic_id} is not in the library"))?;
+    if source.site == target_site.as_str() && source.id == target_id {
+        bail!("Comic {comic_id} is already on {}", target_site.as_str());
+    }
+    if target_id != comic_id && db::check_in_library(db, user_id, target_id).await? {
+        bail!("Comic {target_id} is already in the library");
+    }
+
+    let target = target_site.get_comic(target_id.to_string()).await?;
+    if let Err(err) = db::cache_comic_meta(db, target_site, &target).await {
+        tracing::warn!("failed to cache metadata of comic {}: {err}", target.id);
+    }
+
+    let mut target_chapters: HashMap<_, &ComicChapterBrief> = HashMap::new();
+    for chapter in target
+        .chapter_groups
+        .iter()
+        .flat_map(|group| &group.chapters)
+    {
+        if let Some(key) = chapter_key(chapter.kind, chapter.number) {
+            target_chapters.entry(key).or_insert(chapter);
+        }
+    }
+    // the source may be gone already, so its chapters are identified by the
+    // names saved along with the progress, and by their group when the
+    // source still lists them
+    let source_groups = match SiteId::parse(&source.site) {
+        Some(site) => match site.get_comic(comic_id.to_string()).await {
+            Ok(comic) => comic
+                .chapter_groups
+                .into_iter()
+                .flat_map(|group| {
+                    let name = group.name;
+                    group
+                        .chapters
+                        .into_iter()
+                        .map(move |chapter| (chapter.id, name.clone()))
+                })
+                .collect(),
+            Err(err) => {
+                tracing::warn!("failed to get chapters of comic {comic_id}: {err}");
+                HashMap::new()
+            }
+        },
+        None => HashMap::new(),
+    };
+    let map_chapter = |id: &str, name: &str| {
+        let group_name = source_groups.get(id).map_or("", String::as_str);
+        let number = parse_chapter_number(name, group_name);
+        chapter_key(number.kind, number.number).and_then(|key| target_chapters.get(&key).copied())
+    };
+
+    let now = Utc::now();
+    let txn = db.begin().await?;
+
+    let reads = db::get_chapter_reads(&txn, user_id, comic_id).await?;
+    let mut mapped = vec![];
+    let mut unmapped_chapters = vec![];
+    for read in &reads {
+        match map_chapter(&read.chapter_id, &read.chapter_name) {
+            Some(chapter) => mapped.push(chapter_read::ActiveModel {
+                user_id: Set(user_id),
+                comic_id: Set(target.id.clone()),
+                chapter_id: Set(chapter.id.clone()),
+                chapter_name: Set(chapter.name.clone()),
+                page: Set(read.page),
+                total_pages: Set(read.total_pages),
+                completed: Set(read.completed),
+                read_at: Set(read.read_at),
+                created_at: Set(read.created_at),
+                marked: Set(read.marked),
+            }),
+            None => unmapped_chapters.push(read.chapter_name.clone()),
+        }
+    }
+    let mapped_chapters = mapped.len();
+
+    let comic = lib_comic::ActiveModel {
+        user_id: Set(user_id),
+        id: Set(target.id.clone()),
+        name: Set(target.name.clone()),
+        cover: Set(target.cover.clone()),
+        created_at: Set(source.created_at),
+        updated_at: Set(now),
+        site: Set(target_site.as_str().to_string()),
+    };
+    let comic = if target.id == source.id {
+        comic.update(&txn).await?
+    } else {
+        let comic = comic.insert(&txn).await?;
+        collection_comic::Entity::update_many()
+            .col_expr(collection_comic::Column::ComicId, Expr::value(&target.id))
+            .filter(collection_comic::Column::UserId.eq(user_id))
+            .filter(collection_comic::Column::ComicId.eq(comic_id))
+            .exec(&txn)
+            .await?;
+        comic_note::Entity::update_many()
+            .col_expr(comic_note::Column::ComicId, Expr::value(&target.id))
+            .filter(comic_note::Column::UserId.eq(user_id))
+            .filter(comic_note::Column::ComicId.eq(comic_id))
+            .exec(&txn)
+            .await?;
+        comic_tag::Entity::update_many()
+            .col_expr(comic_tag::Column::ComicId, Expr::value(&target.id))
+            .filter(comic_tag::Column::UserId.eq(user_id))
+            .filter(comic_tag::Column::ComicId.eq(comic_id))
+            .exec(&txn)
+            .await?;
+        lib_comic::Entity::delete_by_id((user_id, comic_id.to_string()))
+            .exec(&txn)
+            .await?;
+        comic
+    };
+
+    chapter_read::Entity::delete_many()
+        .filter(chapter_read::Column::UserId.eq(user_id))
+        .filter(chapter_read::Column::ComicId.eq(comic_id))
+        .exec(&txn)
+        .await?;
+    if !mapped.is_empty() {
+        chapter_read::Entity::insert_many(mapped)
+            .on_conflict(
+                OnConflict::columns([
+                    chapter_read::Column::UserId,
+                    chapter_read::Column::ComicId,
+                    chapter_read::Column::ChapterId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .do_nothing()
+            .exec(&txn)
+            .await?;
+    }
+
+    if let Some(item) = history::Entity::find_by_id((user_id, comic_id.to_string()))
+        .one(&txn)
+        .await?
+    {
+        history::Entity::delete_by_id((user_id, comic_id.to_string()))
+            .exec(&txn)
+            .await?;
+
+        // fall back to the latest mapped chapter when the last read one has
+        // no equivalent
+        let chapter =
+            map_chapter(&item.chapter_id, &item.chapter_name).map(|chapter| (chapter, item.page));
+        let chapter = chapter.or_else(|| {
+            reads
+                .iter()
+                .filter_map(|read| {
+                    Some((
+                        map_chapter(&read.chapter_id, &read.chapter_name)?,
+                        read.read_at,
+                    ))
+                })
+                .max_by_key(|(_, read_at)| *read_at)
+                .map(|(chapter, _)| (chapter, 0))
+        });
+        let newer_exists = history::Entity::find_by_id((user_id, target.id.clone()))
+            .one(&txn)
+            .await?
+            .is_some_and(|existing| existing.updated_at > item.updated_at);
+
+        if let (Some((chapter, page)), false) = (chapter, newer_exists) {
+            history::Entity::delete_by_id((user_id, target.id.clone()))
+                .exec(&txn)
+                .await?;
+            let mut item = item.into_active_model();
+            item.comic_id = Set(target.id.clone());
+            item.comic_name = Set(target.name.clone());
+            item.chapter_id = Set(chapter.id.clone());
+            item.chapter_name = Set(chapter.name.clone());
+            item.page = Set(page);
+            item.insert(&txn).await?;
+        }
+    }
+
+    txn.commit().await?;
+
+    Ok(MigrationResult {
+        comic,
+        mapped_chapters,
+        unmapped_chapters,
+    })
+}
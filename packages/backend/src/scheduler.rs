@@ -0,0 +1,36 @@
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+
+use crate::{config::Config, db, shutdown::Shutdown, throttle::AUDIT_RETENTION};
+
+/// Deletes expired sessions and share links, and login attempts past their
+/// retention, every `scheduler.cleanup_interval_secs`. A cleanup that is
+/// running when shutting down is finished first.
+pub fn spawn_cleanup(
+    db: DatabaseConnection,
+    config: &Config,
+    mut shutdown: Shutdown,
+) -> tokio::task::JoinHandle<()> {
+    let mut interval = tokio::time::interval(config.cleanup_interval());
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.wait() => break,
+            }
+            if let Err(err) = cleanup(&db).await {
+                tracing::warn!("cleanup failed: {err}");
+            }
+        }
+    })
+}
+
+async fn cleanup(db: &DatabaseConnection) -> anyhow::Result<()> {
+    let sessions = db::delete_expired_sessions(db).await?;
+    let share_links = db::delete_expired_share_links(db).await?;
+    let login_attempts = db::delete_login_attempts_before(db, Utc::now() - AUDIT_RETENTION).await?;
+    tracing::debug!(
+        "cleaned up {sessions} sessions, {share_links} share links and {login_attempts} login attempts"
+    );
+    Ok(())
+}
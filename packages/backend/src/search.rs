@@ -0,0 +1,172 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde::Serialize;
+use tokio::time::{timeout, Instant};
+
+use crate::site::{ComicBrief, SiteId};
+
+pub const DEFAULT_SITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest timeout clients can ask for, so a search cannot keep a task per
+/// site running for long.
+pub const MAX_SITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcedComic {
+    pub site: SiteId,
+    pub comic: ComicBrief,
+}
+
+/// Comics from different sites that are likely the same one.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregatedComic {
+    pub name: String,
+    pub author: Vec<String>,
+    pub sources: Vec<SourcedComic>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteSearchStatus {
+    pub site: SiteId,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub count: usize,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregatedSearch {
+    pub list: Vec<AggregatedComic>,
+    pub sites: Vec<SiteSearchStatus>,
+}
+
+/// Searches every site concurrently. A site failing or exceeding
+/// `site_timeout` only drops its own results, which is reported in
+/// [`AggregatedSearch::sites`].
+pub async fn search_all(
+    sites: &[SiteId],
+    keyword: &str,
+    site_timeout: Duration,
+) -> AggregatedSearch {
+    let handles = sites
+        .iter()
+        .map(|&site| {
+            let keyword = keyword.to_string();
+            let handle = tokio::spawn(async move {
+                let start = Instant::now();
+                let res = timeout(site_timeout, site.search_comic(keyword)).await;
+                (res, start.elapsed())
+            });
+            (site, handle)
+        })
+        .collect::<Vec<_>>();
+
+    let mut results = vec![];
+    let mut statuses = vec![];
+
+    for (site, handle) in handles {
+        let (res, elapsed) = match handle.await {
+            Ok(res) => res,
+            Err(err) => {
+                statuses.push(SiteSearchStatus {
+                    site,
+                    ok: false,
+                    error: Some(err.to_string()),
+                    count: 0,
+                    elapsed_ms: 0,
+                });
+                continue;
+            }
+        };
+        let elapsed_ms = elapsed.as_millis() as u64;
+
+        match res {
+            Ok(Ok(list)) => {
+                statuses.push(SiteSearchStatus {
+                    site,
+                    ok: true,
+                    error: None,
+                    count: list.len(),
+                    elapsed_ms,
+                });
+                results.extend(list.into_iter().map(|comic| SourcedComic { site, comic }));
+            }
+            Ok(Err(err)) => statuses.push(SiteSearchStatus {
+                site,
+                ok: false,
+                error: Some(err.to_string()),
+                count: 0,
+                elapsed_ms,
+            }),
+            Err(_) => statuses.push(SiteSearchStatus {
+                site,
+                ok: false,
+                error: Some(format!("Timed out after {}ms", site_timeout.as_millis())),
+                count: 0,
+                elapsed_ms,
+            }),
+        }
+    }
+
+    AggregatedSearch {
+        list: group_comics(results),
+        sites: statuses,
+    }
+}
+
+/// Groups comics with the same normalized title and author, keeping the
+/// order in which each group first appeared.
+fn group_comics(comics: Vec<SourcedComic>) -> Vec<AggregatedComic> {
+    let mut groups: Vec<AggregatedComic> = vec![];
+    let mut index_by_key: HashMap<_, usize> = HashMap::new();
+
+    for item in comics {
+        let key = (
+            normalize_title(&item.comic.name),
+            normalize_authors(&item.comic.author),
+        );
+
+        match index_by_key.get(&key) {
+            Some(&index) => groups[index].sources.push(item),
+            None => {
+                index_by_key.insert(key, groups.len());
+                groups.push(AggregatedComic {
+                    name: item.comic.name.clone(),
+                    author: item.comic.author.clone(),
+                    sources: vec![item],
+                });
+            }
+        }
+    }
+
+    groups
+}
+
+/// Lowercases the title and drops whitespace, punctuation and full-width
+/// variants, so `One Piece`, `ONE PIECE` and `ＯＮＥ・ＰＩＥＣＥ` compare equal.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| match c {
+            // full-width ASCII variants
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn normalize_authors(author: &[String]) -> Vec<String> {
+    let mut author = author
+        .iter()
+        .map(|name| normalize_title(name))
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+    author.sort();
+    author
+}
@@ -0,0 +1,117 @@
+use std::net::{IpAddr, SocketAddr};
+
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{request::Parts, HeaderMap},
+};
+
+use super::{types::AppError, AppState};
+
+#[derive(Debug, Clone, Copy)]
+struct IpRange {
+    addr: IpAddr,
+    prefix: u32,
+}
+
+impl IpRange {
+    /// Accepts a single address or a CIDR range, e.g. `10.0.0.0/8`.
+    fn parse(value: &str) -> Result<Self> {
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| anyhow!("Invalid trusted proxy {value}"))?
+            .to_canonical();
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u32>()
+                .ok()
+                .filter(|prefix| *prefix <= bits)
+                .ok_or_else(|| anyhow!("Invalid trusted proxy {value}"))?,
+            None => bits,
+        };
+        Ok(Self { addr, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        let (net, ip, bits) = match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                (u32::from(net) as u128, u32::from(ip) as u128, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => (u128::from(net), u128::from(ip), 128),
+            _ => return false,
+        };
+        let shift = bits - self.prefix;
+        shift >= bits || net >> shift == ip >> shift
+    }
+}
+
+/// Reverse proxies whose `X-Forwarded-For` header is trusted.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Vec<IpRange>);
+
+impl TrustedProxies {
+    /// Nothing is trusted when `ranges` is empty.
+    pub fn parse(ranges: &[String]) -> Result<Self> {
+        let ranges = ranges
+            .iter()
+            .map(|value| IpRange::parse(value))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self(ranges))
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|range| range.contains(ip))
+    }
+
+    /// Walks `X-Forwarded-For` from the nearest hop, so clients cannot spoof
+    /// their address by sending the header themselves.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let peer = peer.to_canonical();
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let hops = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|hop| hop.trim().parse::<IpAddr>().map(|ip| ip.to_canonical()))
+            .collect::<Vec<_>>();
+
+        let mut client = peer;
+        for hop in hops.into_iter().rev() {
+            // a malformed entry could have been written by anyone
+            let Ok(hop) = hop else {
+                break;
+            };
+            client = hop;
+            if !self.is_trusted(hop) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Address of the client, as seen through trusted proxies.
+pub struct ClientIp(pub IpAddr);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let ConnectInfo(peer) = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .ok_or_else(|| anyhow!("Missing connection info"))?;
+        Ok(ClientIp(
+            state.trusted_proxies.client_ip(peer.ip(), &parts.headers),
+        ))
+    }
+}
@@ -1,19 +1,29 @@
-use std::env;
-
 use anyhow::anyhow;
 use axum::{
     body::Body,
-    extract::Request,
+    extract::{Request, State},
+    http::{header, HeaderMap},
     middleware::Next,
-    response::{IntoResponse, Response},
-    Json,
+    response::Response,
 };
-use axum_extra::extract::CookieJar;
+use axum_extra::extract::SignedCookieJar;
+use chrono::Utc;
 use http_body_util::BodyExt;
 use reqwest::StatusCode;
 use serde_json::Value;
 
-use super::types::{AppError, Resp};
+use entity::{api_token, session, user};
+
+use crate::{
+    auth::{hash_token, token_scopes, Role, Scope, SESSION_COOKIE, TOUCH_INTERVAL},
+    db,
+};
+
+use super::{
+    share::resolve_share_link,
+    types::{AppError, Resp},
+    AppState,
+};
 
 pub async fn wrap_response_middleware(request: Request, next: Next) -> Result<Response, AppError> {
     let response = next.run(request).await;
@@ -22,6 +32,11 @@ pub async fn wrap_response_middleware(request: Request, next: Next) -> Result<Re
         return Ok(response);
     }
 
+    // downloads are handed to the client as they are
+    if response.headers().contains_key("content-disposition") {
+        return Ok(response);
+    }
+
     match response.headers().get("content-type") {
         Some(content_type) => {
             let Ok(content_type) = content_type.to_str() else {
@@ -62,40 +77,120 @@ pub async fn wrap_response_middleware(request: Request, next: Next) -> Result<Re
     Ok(new_response)
 }
 
-pub async fn password_validate_middleware(
-    jar: CookieJar,
-    request: Request,
+/// Resolves the `Authorization: Bearer` API token, or else the session
+/// cookie, to a user. Handlers receive it through `Extension<user::Model>`,
+/// along with the `Extension<api_token::Model>` or `Extension<session::Model>`
+/// it was authenticated with. Requests without either may still open what a
+/// share link covers, in which case only `Extension<share_link::Model>` is
+/// set.
+pub async fn auth_middleware(
+    State(state): State<AppState>,
+    jar: SignedCookieJar,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    let mut fail = true;
+    let db = &state.db;
+    let now = Utc::now();
+
+    if let Some(token) = bearer_token(request.headers()) {
+        let Some((token, user)) = db::get_api_token_by_hash(db, &hash_token(token)).await? else {
+            return Err(AppError::new(
+                StatusCode::UNAUTHORIZED,
+                anyhow!("Invalid API token"),
+            ));
+        };
+        if token
+            .last_used_at
+            .is_none_or(|last_used_at| now - last_used_at > TOUCH_INTERVAL)
+        {
+            db::touch_api_token(db, token.id).await?;
+        }
 
-    let coconfigured_password = env::var("PASSWORD").unwrap_or("".to_string());
-    let configured_password = coconfigured_password.trim();
+        request.extensions_mut().insert(user);
+        request.extensions_mut().insert(token);
+        return Ok(next.run(request).await);
+    }
 
-    if configured_password.is_empty() {
-        fail = false;
-    } else {
-        let received_password = jar
-            .get("comiya-password")
-            .map(|v| v.value_trimmed().trim())
-            .unwrap_or("");
+    // the signature is checked in constant time before touching the database
+    let found = match jar.get(SESSION_COOKIE) {
+        Some(cookie) => db::get_session_by_token_hash(db, &hash_token(cookie.value())).await?,
+        None => None,
+    };
 
-        if received_password == configured_password {
-            fail = false;
+    let Some((session, user)) = found else {
+        if let Some(link) = resolve_share_link(&state, request.uri()).await? {
+            request.extensions_mut().insert(link);
+            return Ok(next.run(request).await);
         }
+        return Err(AppError::new(
+            StatusCode::UNAUTHORIZED,
+            anyhow!("Not logged in"),
+        ));
+    };
+
+    if now - session.last_seen_at > TOUCH_INTERVAL {
+        db::touch_session(db, session.id).await?;
     }
 
-    if fail {
-        return Ok((
-            StatusCode::FORBIDDEN,
-            Json(Resp::<()> {
-                code: -1,
-                msg: "Invalid password".to_string(),
-                data: None,
-            }),
-        )
-            .into_response());
+    request.extensions_mut().insert(user);
+    request.extensions_mut().insert(session);
+    Ok(next.run(request).await)
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then_some(token.trim())
+}
+
+/// Limits API tokens to the routes their scopes cover.
+pub async fn scope_middleware(
+    State(scope): State<Scope>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(token) = request.extensions().get::<api_token::Model>() {
+        if !token_scopes(token).contains(&scope) {
+            return Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                anyhow!("API token lacks the {} scope", scope.as_str()),
+            ));
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+/// Limits users to the routes their role allows, e.g. keeps guests from
+/// changing anything.
+pub async fn role_middleware(
+    State(role): State<Role>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let allowed = request
+        .extensions()
+        .get::<user::Model>()
+        .is_some_and(|user| Role::of(user) >= role);
+    if !allowed {
+        let error = match role {
+            Role::Admin => anyhow!("Only admins can do this"),
+            _ => anyhow!("Guests can only browse and read"),
+        };
+        return Err(AppError::new(StatusCode::FORBIDDEN, error));
     }
+    Ok(next.run(request).await)
+}
 
+/// Keeps API tokens away from account settings, e.g. so a leaked token cannot
+/// be used to create new ones.
+pub async fn session_only_middleware(request: Request, next: Next) -> Result<Response, AppError> {
+    if request.extensions().get::<session::Model>().is_none() {
+        return Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            anyhow!("Only available when logged in"),
+        ));
+    }
     Ok(next.run(request).await)
 }
@@ -1,31 +1,104 @@
-use anyhow::Result;
-use migration::{
-    sea_orm::{Database, DatabaseConnection},
-    Migrator, MigratorTrait,
-};
+use std::{future::IntoFuture, net::SocketAddr, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use axum::extract::FromRef;
+use axum_extra::extract::cookie::Key;
+use migration::sea_orm::DatabaseConnection;
 use router::get_router;
+use share::SharedImages;
+
+use crate::{auth, config::Config, db, scheduler, shutdown};
 
+mod client_ip;
 mod middleware;
 mod router;
+mod share;
 mod types;
 
+pub use client_ip::TrustedProxies;
+
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseConnection,
+    /// Signs session cookies.
+    pub cookie_key: Key,
+    pub trusted_proxies: Arc<TrustedProxies>,
+    pub shared_images: Arc<SharedImages>,
+    pub config: Arc<Config>,
+}
+
+impl FromRef<AppState> for Key {
+    fn from_ref(state: &AppState) -> Self {
+        state.cookie_key.clone()
+    }
 }
 
-pub async fn run() -> Result<()> {
-    let db = Database::connect(env!("DATABASE_URL")).await?;
+pub async fn run(config: Config) -> Result<()> {
+    let db = db::connect(&config.database_url).await?;
+    auth::init_passwords(&db, config.auth.password.as_deref()).await?;
 
-    Migrator::up(&db, None).await?;
+    let cookie_key = auth::load_cookie_key(&db).await?;
+    let (trigger, mut shutdown) = shutdown::channel();
+    let cleanup = scheduler::spawn_cleanup(db.clone(), &config, shutdown.clone());
 
-    let state = AppState { db };
+    let state = AppState {
+        db,
+        cookie_key,
+        trusted_proxies: Arc::new(TrustedProxies::parse(&config.auth.trusted_proxies)?),
+        shared_images: Arc::new(SharedImages::new(config.cache.shared_images)),
+        config: Arc::new(config),
+    };
 
-    let app = get_router().with_state(state);
+    let app = get_router(state.clone()).with_state(state.clone());
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await?;
+    let listener = tokio::net::TcpListener::bind(state.config.bind)
+        .await
+        .map_err(|err| anyhow!("Failed to bind {}: {err}", state.config.bind))?;
     tracing::info!("listening on {}", listener.local_addr()?);
-    axum::serve(listener, app).await?;
 
+    let mut server = tokio::spawn(
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move { shutdown.wait().await })
+        .into_future(),
+    );
+
+    // the server only ends by itself on errors, which still get the shutdown
+    // below
+    let stopped = tokio::select! {
+        res = &mut server => Some(res),
+        _ = shutdown::signal() => None,
+    };
+
+    // stop accepting connections and give in-flight requests some time, while
+    // background jobs finish what they are doing
+    tracing::info!("shutting down");
+    trigger.trigger();
+    let res = match stopped {
+        Some(res) => res,
+        None => {
+            let timeout = state.config.shutdown_timeout();
+            match tokio::time::timeout(timeout, &mut server).await {
+                Ok(res) => res,
+                Err(_) => {
+                    tracing::warn!(
+                        "requests still running after {}s, dropping them",
+                        timeout.as_secs()
+                    );
+                    server.abort();
+                    Ok(Ok(()))
+                }
+            }
+        }
+    };
+    if let Err(err) = cleanup.await {
+        tracing::warn!("cleanup task failed: {err}");
+    }
+
+    state.db.close().await?;
+    tracing::info!("database closed");
+    res??;
     Ok(())
 }
@@ -1,62 +1,209 @@
-use std::env;
+use std::time::Duration;
 
+use anyhow::anyhow;
 use axum::{
-    body::Body,
-    extract::{Query, State},
-    middleware::from_fn,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::{from_fn, from_fn_with_state},
     response::IntoResponse,
     routing::{get, post, MethodRouter},
-    Json, Router,
+    Extension, Json, Router,
+};
+use axum_extra::extract::{
+    cookie::{Cookie, SameSite},
+    SignedCookieJar,
+};
+use chrono::{Days, FixedOffset, NaiveDate, Utc};
+use entity::{
+    api_token, chapter_read, collection, comic_note, history, lib_comic, login_attempt, read_event,
+    session, share_link, user,
 };
-use entity::{history, lib_comic};
 use sea_orm::Set;
 use tower_http::services::{ServeDir, ServeFile};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    auth::{self, Role, Scope, MAX_API_TOKEN_DAYS, SESSION_COOKIE},
+    backup::{self, Backup, ImportSummary, MergeStrategy},
+    chapter::{find_chapter, next_unread, select_chapters, ChapterScope},
+    check::{check_site, site_health, SiteHealth, SiteStatus},
+    config::Config,
     db,
-    server::types::AppResult,
-    site::{Comic, ComicBrief, ComicChapter, Manhuagui, Site},
+    library::{list_library, LibraryFilter, LibraryItem},
+    migrate::{self, MigrationCandidates, MigrationResult},
+    search::{search_all, AggregatedSearch, DEFAULT_SITE_TIMEOUT, MAX_SITE_TIMEOUT},
+    server::types::{AppError, AppResult},
+    site::{
+        http_client, BrowseFilter, BrowseFilterGroup, Comic, ComicBrief, ComicChapter,
+        ComicChapterBrief, ComicComment, PageList, RankType, SiteId,
+    },
+    stats::{compute_stats, heartbeat_duration, ReadingStats},
+    tachiyomi::{self, TachiyomiImportSummary},
+    throttle::{self, LoginResult},
 };
 
 use super::{
-    middleware::{password_validate_middleware, wrap_response_middleware},
+    client_ip::ClientIp,
+    middleware::{
+        auth_middleware, role_middleware, scope_middleware, session_only_middleware,
+        wrap_response_middleware,
+    },
+    share::{ShareAccess, DEFAULT_SHARE_LINK_DAYS, MAX_SHARE_LINK_DAYS},
     AppState,
 };
 
-pub fn get_router() -> Router<AppState> {
-    let auth_api_router = Router::new()
+pub fn get_router(state: AppState) -> Router<AppState> {
+    let read_router = Router::new()
         .merge(search_comic())
+        .merge(search_all_sites())
+        .merge(get_latest_comics())
+        .merge(get_rank_comics())
+        .merge(browse_comics())
+        .merge(get_browse_filters())
         .merge(get_comic())
+        .merge(get_related_comics())
+        .merge(get_comic_comments())
         .merge(get_chapter())
         .merge(proxy_image())
-        .merge(upsert_history())
         .merge(get_history())
-        .merge(delete_history())
+        .merge(get_history_trash())
         .merge(get_library())
-        .merge(add_to_library())
-        .merge(remove_from_library())
         .merge(check_in_library())
+        .merge(get_migration_candidates())
+        .merge(get_collections())
+        .merge(get_comic_collections())
+        .merge(get_comic_note())
+        .merge(get_tags())
         .merge(get_comic_history())
-        .layer(from_fn(password_validate_middleware));
+        .merge(get_next_unread_chapter())
+        .merge(get_read_chapters())
+        .merge(get_chapter_progress())
+        .merge(get_reading_stats())
+        .merge(get_current_user())
+        .merge(get_site_status())
+        .route_layer(from_fn_with_state(Scope::Read, scope_middleware));
+
+    let downloads_router = Router::new()
+        .merge(export_backup().route_layer(from_fn_with_state(Role::Member, role_middleware)))
+        .route_layer(from_fn_with_state(Scope::Downloads, scope_middleware));
+
+    let library_router = Router::new()
+        .merge(upsert_history())
+        .merge(delete_history())
+        .merge(restore_history())
+        .merge(purge_history())
+        .merge(purge_old_history())
+        .merge(add_to_library())
+        .merge(remove_from_library())
+        .merge(migrate_comic())
+        .merge(create_collection())
+        .merge(rename_collection())
+        .merge(delete_collection())
+        .merge(reorder_collections())
+        .merge(set_comic_collections())
+        .merge(reorder_collection_comics())
+        .merge(upsert_comic_note())
+        .merge(delete_comic_note())
+        .merge(set_comic_tags())
+        .merge(mark_chapters())
+        .merge(import_backup())
+        .merge(import_tachiyomi_backup())
+        .route_layer(from_fn_with_state(Scope::Library, scope_middleware))
+        .route_layer(from_fn_with_state(Role::Member, role_middleware));
+
+    let admin_router = Router::new()
+        .merge(get_users())
+        .merge(get_login_attempts())
+        .merge(create_user())
+        .merge(delete_user())
+        .merge(reset_user_password())
+        .route_layer(from_fn_with_state(Scope::Admin, scope_middleware))
+        .route_layer(from_fn_with_state(Role::Admin, role_middleware));
+
+    let account_router = Router::new()
+        .merge(change_password())
+        .merge(get_sessions())
+        .merge(revoke_session())
+        .merge(get_api_tokens())
+        .merge(create_api_token())
+        .merge(revoke_api_token())
+        .merge(get_share_links())
+        .merge(create_share_link())
+        .merge(revoke_share_link())
+        .route_layer(from_fn(session_only_middleware))
+        .route_layer(from_fn_with_state(Role::Member, role_middleware));
+
+    let auth_api_router = Router::new()
+        .merge(read_router)
+        .merge(downloads_router)
+        .merge(library_router)
+        .merge(admin_router)
+        .merge(account_router)
+        .layer(from_fn_with_state(state.clone(), auth_middleware));
 
     let api_router = auth_api_router
-        .merge(check_password())
+        .merge(login())
+        .merge(logout())
         .layer(from_fn(wrap_response_middleware));
 
-    let serve_dir = ServeDir::new("dist").fallback(ServeFile::new("dist/index.html"));
+    let static_dir = &state.config.static_dir;
+    let serve_dir =
+        ServeDir::new(static_dir).fallback(ServeFile::new(static_dir.join("index.html")));
 
     Router::new()
+        .merge(healthz())
+        .merge(readyz())
         .nest("/api", api_router)
         .fallback_service(serve_dir)
 }
 
+/// Up as long as the process is, for liveness probes.
+fn healthz() -> Router<AppState> {
+    async fn handler() -> Json<HealthStatus> {
+        Json(HealthStatus {
+            status: "ok",
+            error: None,
+        })
+    }
+
+    route("/healthz", get(handler))
+}
+
+/// Ready once the database can be reached and is migrated.
+fn readyz() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+    ) -> (StatusCode, Json<HealthStatus>) {
+        match db::check_ready(&db).await {
+            Ok(()) => (
+                StatusCode::OK,
+                Json(HealthStatus {
+                    status: "ok",
+                    error: None,
+                }),
+            ),
+            Err(err) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(HealthStatus {
+                    status: "unavailable",
+                    error: Some(err.to_string()),
+                }),
+            ),
+        }
+    }
+
+    route("/readyz", get(handler))
+}
+
 fn search_comic() -> Router<AppState> {
     async fn handler(
+        State(AppState { config, .. }): State<AppState>,
         Query(SearchComicQuery { keyword }): Query<SearchComicQuery>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
     ) -> AppResult<Json<Vec<ComicBrief>>> {
-        let site = Manhuagui;
+        let site = enabled_site(&config, site.as_deref())?;
         let list = site.search_comic(keyword).await?;
         Ok(Json(list))
     }
@@ -64,27 +211,158 @@ fn search_comic() -> Router<AppState> {
     route("/search_comic", get(handler))
 }
 
+fn search_all_sites() -> Router<AppState> {
+    async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(SearchAllSitesQuery {
+            keyword,
+            sites,
+            timeout_ms,
+        }): Query<SearchAllSitesQuery>,
+    ) -> AppResult<Json<AggregatedSearch>> {
+        let mut targets = config.enabled_sites();
+        if let Some(sites) = sites {
+            let requested = sites
+                .split(',')
+                .filter_map(SiteId::parse)
+                .collect::<Vec<_>>();
+            targets.retain(|site| requested.contains(site));
+        }
+        let site_timeout = timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_SITE_TIMEOUT)
+            .min(MAX_SITE_TIMEOUT);
+
+        let res = search_all(&targets, &keyword, site_timeout).await;
+        Ok(Json(res))
+    }
+
+    route("/search_all_sites", get(handler))
+}
+
+fn get_latest_comics() -> Router<AppState> {
+    async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(PageQuery { page }): Query<PageQuery>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
+    ) -> AppResult<Json<PageList<ComicBrief>>> {
+        let site = enabled_site(&config, site.as_deref())?;
+        let res = site.get_latest(page).await?;
+        Ok(Json(res))
+    }
+
+    route("/get_latest_comics", get(handler))
+}
+
+fn get_rank_comics() -> Router<AppState> {
+    async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(GetRankComicsQuery { rank_type }): Query<GetRankComicsQuery>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
+    ) -> AppResult<Json<Vec<ComicBrief>>> {
+        let site = enabled_site(&config, site.as_deref())?;
+        let list = site.get_rank(rank_type).await?;
+        Ok(Json(list))
+    }
+
+    route("/get_rank_comics", get(handler))
+}
+
+fn browse_comics() -> Router<AppState> {
+    async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(PageQuery { page }): Query<PageQuery>,
+        Query(filter): Query<BrowseFilter>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
+    ) -> AppResult<Json<PageList<ComicBrief>>> {
+        let site = enabled_site(&config, site.as_deref())?;
+        let res = site.browse(filter, page).await?;
+        Ok(Json(res))
+    }
+
+    route("/browse_comics", get(handler))
+}
+
+fn get_browse_filters() -> Router<AppState> {
+    async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
+    ) -> AppResult<Json<Vec<BrowseFilterGroup>>> {
+        let site = enabled_site(&config, site.as_deref())?;
+        Ok(Json(site.browse_filters()))
+    }
+
+    route("/get_browse_filters", get(handler))
+}
+
 fn get_comic() -> Router<AppState> {
     async fn handler(
+        State(AppState { config, db, .. }): State<AppState>,
         Query(GetComicBriefQuery { id }): Query<GetComicBriefQuery>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
     ) -> AppResult<Json<Comic>> {
-        let site = Manhuagui;
+        let site = enabled_site(&config, site.as_deref())?;
         let res = site.get_comic(id).await?;
+
+        // the page itself is still usable when caching fails
+        if let Err(err) = db::cache_comic_meta(&db, site, &res).await {
+            tracing::warn!("failed to cache metadata of comic {}: {err}", res.id);
+        }
+
         Ok(Json(res))
     }
 
     route("/get_comic", get(handler))
 }
 
+fn get_related_comics() -> Router<AppState> {
+    async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(GetComicBriefQuery { id }): Query<GetComicBriefQuery>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
+    ) -> AppResult<Json<Vec<ComicBrief>>> {
+        let site = enabled_site(&config, site.as_deref())?;
+        let list = site.get_related(id).await?;
+        Ok(Json(list))
+    }
+
+    route("/get_related_comics", get(handler))
+}
+
+fn get_comic_comments() -> Router<AppState> {
+    async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(GetComicBriefQuery { id }): Query<GetComicBriefQuery>,
+        Query(PageQuery { page }): Query<PageQuery>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
+    ) -> AppResult<Json<PageList<ComicComment>>> {
+        let site = enabled_site(&config, site.as_deref())?;
+        let res = site.get_comments(id, page).await?;
+        Ok(Json(res))
+    }
+
+    route("/get_comic_comments", get(handler))
+}
+
 fn get_chapter() -> Router<AppState> {
     async fn handler(
+        State(AppState {
+            config,
+            shared_images,
+            ..
+        }): State<AppState>,
+        ShareAccess(share_link): ShareAccess,
         Query(GetChapterImagesQuery {
             comic_id,
             chapter_id,
         }): Query<GetChapterImagesQuery>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
     ) -> AppResult<Json<ComicChapter>> {
-        let site = Manhuagui;
+        let site = enabled_site(&config, site.as_deref())?;
         let chapter = site.get_chapter(comic_id, chapter_id).await?;
+        if let Some(link) = share_link {
+            shared_images.allow(link.id, &chapter.images);
+        }
         Ok(Json(chapter))
     }
 
@@ -93,12 +371,15 @@ fn get_chapter() -> Router<AppState> {
 
 fn proxy_image() -> Router<AppState> {
     async fn handler(
+        State(AppState { config, .. }): State<AppState>,
+        Query(SiteQuery { site }): Query<SiteQuery>,
         Query(ProxyImageQuery { url }): Query<ProxyImageQuery>,
     ) -> AppResult<impl IntoResponse> {
+        let site = enabled_site(&config, site.as_deref())?;
         let decoded_url = urlencoding::decode(&url)?;
-        let resp = reqwest::Client::new()
+        let resp = http_client()
             .get(decoded_url.as_ref())
-            .header("Referer", "https://www.manhuagui.com/")
+            .header("Referer", site.referer())
             .send()
             .await?;
         let headers = resp.headers().to_owned();
@@ -110,238 +391,1751 @@ fn proxy_image() -> Router<AppState> {
     route("/proxy_image", get(handler))
 }
 
-fn check_password() -> Router<AppState> {
+fn login() -> Router<AppState> {
     async fn handler(
-        Json(CheckPasswordData { password }): Json<CheckPasswordData>,
-    ) -> AppResult<Json<CheckPasswordResp>> {
-        let coconfigured_password = env::var("PASSWORD").unwrap_or("".to_string());
-        let configured_password = coconfigured_password.trim();
+        State(AppState { db, config, .. }): State<AppState>,
+        ClientIp(ip): ClientIp,
+        headers: HeaderMap,
+        jar: SignedCookieJar,
+        Json(LoginData { username, password }): Json<LoginData>,
+    ) -> AppResult<(SignedCookieJar, Json<user::Model>)> {
+        let username = username.trim();
+        let ip = ip.to_string();
 
-        Ok(Json(CheckPasswordResp {
-            valid: configured_password.is_empty() || password.trim() == configured_password,
-        }))
-    }
+        if let Some(remaining) = throttle::check_lockout(&db, username, &ip).await? {
+            throttle::record_attempt(&db, username, &ip, LoginResult::Locked).await?;
+            return Err(AppError::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                anyhow!(
+                    "Too many failed attempts, try again in {} seconds",
+                    remaining.num_seconds().max(1)
+                ),
+            ));
+        }
 
-    route("/check_password", post(handler))
-}
+        let user = db::get_user_by_username(&db, username).await?;
+        let Some(user) = auth::verify_login(user, &password) else {
+            throttle::record_attempt(&db, username, &ip, LoginResult::InvalidCredentials).await?;
+            return Err(AppError::new(
+                StatusCode::UNAUTHORIZED,
+                anyhow!("Invalid username or password"),
+            ));
+        };
+        throttle::record_attempt(&db, username, &ip, LoginResult::Success).await?;
 
-fn upsert_history() -> Router<AppState> {
-    async fn handler(
-        State(AppState { db }): State<AppState>,
-        Json(UpsertHistoryData {
-            comic_id,
-            chapter_id,
-            comic_name,
-            chapter_name,
-            page,
-            visible,
-        }): Json<UpsertHistoryData>,
-    ) -> AppResult<Json<()>> {
-        db::upsert_history(
+        let token = auth::new_session_token();
+        let now = Utc::now();
+        let session_ttl = config.session_ttl();
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        db::create_session(
             &db,
-            history::ActiveModel {
-                comic_id: Set(comic_id),
-                chapter_id: Set(chapter_id),
-                comic_name: Set(comic_name),
-                chapter_name: Set(chapter_name),
-                page: Set(page),
-                visible: Set(visible),
-                updated_at: Set(chrono::Utc::now()),
+            session::ActiveModel {
+                token_hash: Set(auth::hash_token(&token)),
+                user_id: Set(user.id),
+                user_agent: Set(user_agent.to_string()),
+                ip: Set(ip),
+                created_at: Set(now),
+                last_seen_at: Set(now),
+                expires_at: Set(now + session_ttl),
                 ..Default::default()
             },
         )
         .await?;
 
-        Ok(Json(()))
+        let cookie = Cookie::build((SESSION_COOKIE, token))
+            .path("/")
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .max_age(time::Duration::seconds(session_ttl.num_seconds()));
+        Ok((jar.add(cookie), Json(user)))
     }
 
-    route("/upsert_history", post(handler))
+    route("/login", post(handler))
 }
 
-fn get_history() -> Router<AppState> {
+fn logout() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        jar: SignedCookieJar,
+    ) -> AppResult<(SignedCookieJar, Json<()>)> {
+        if let Some(cookie) = jar.get(SESSION_COOKIE) {
+            db::delete_session_by_token_hash(&db, &auth::hash_token(cookie.value())).await?;
+        }
+        Ok((
+            jar.remove(Cookie::build(SESSION_COOKIE).path("/")),
+            Json(()),
+        ))
+    }
+
+    route("/logout", post(handler))
+}
+
+fn get_current_user() -> Router<AppState> {
+    async fn handler(Extension(user): Extension<user::Model>) -> AppResult<Json<user::Model>> {
+        Ok(Json(user))
+    }
+
+    route("/get_current_user", get(handler))
+}
+
+/// The latest scrape outcomes of each enabled site, and with `check` the
+/// result of checking each site right away.
+fn get_site_status() -> Router<AppState> {
     async fn handler(
-        State(AppState { db }): State<AppState>,
-    ) -> AppResult<Json<Vec<history::Model>>> {
-        let list = db::get_history(&db).await?;
+        State(AppState { config, .. }): State<AppState>,
+        Query(GetSiteStatusQuery { check }): Query<GetSiteStatusQuery>,
+    ) -> AppResult<Json<Vec<SiteStatusItem>>> {
+        let sites = config.enabled_sites();
+        let mut checks = vec![];
+        if check.unwrap_or(false) {
+            let handles = sites
+                .iter()
+                .map(|&site| tokio::spawn(check_site(site)))
+                .collect::<Vec<_>>();
+            for handle in handles {
+                checks.push(handle.await?);
+            }
+        }
+
+        let mut checks = checks.into_iter();
+        let list = sites
+            .into_iter()
+            .map(|site| SiteStatusItem {
+                site,
+                // checked first, so the health includes the check
+                check: checks.next(),
+                health: site_health(site),
+            })
+            .collect();
         Ok(Json(list))
     }
 
-    route("/get_history", get(handler))
+    route("/get_site_status", get(handler))
 }
 
-fn delete_history() -> Router<AppState> {
+fn change_password() -> Router<AppState> {
     async fn handler(
-        State(AppState { db }): State<AppState>,
-        Json(DeleteHistoryData { comic_id }): Json<DeleteHistoryData>,
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Extension(session): Extension<session::Model>,
+        Json(ChangePasswordData {
+            current_password,
+            new_password,
+        }): Json<ChangePasswordData>,
     ) -> AppResult<Json<()>> {
-        db::delete_history(&db, &comic_id).await?;
+        if !auth::verify_password(&current_password, &user.password_hash) {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Current password is incorrect"),
+            ));
+        }
+        auth::validate_password(&new_password)?;
+        db::set_user_password(&db, user.id, &auth::hash_password(&new_password)?).await?;
+        // other devices have to log in with the new password
+        db::delete_user_sessions(&db, user.id, Some(session.id)).await?;
         Ok(Json(()))
     }
 
-    route("/delete_history", post(handler))
+    route("/change_password", post(handler))
 }
 
-fn get_library() -> Router<AppState> {
+fn get_sessions() -> Router<AppState> {
     async fn handler(
-        State(AppState { db }): State<AppState>,
-    ) -> AppResult<Json<Vec<lib_comic::Model>>> {
-        let list = db::get_library(&db).await?;
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Extension(current): Extension<session::Model>,
+    ) -> AppResult<Json<Vec<SessionItem>>> {
+        let list = db::get_user_sessions(&db, user.id)
+            .await?
+            .into_iter()
+            .map(|session| SessionItem {
+                current: session.id == current.id,
+                session,
+            })
+            .collect();
         Ok(Json(list))
     }
 
-    route("/get_library", get(handler))
+    route("/get_sessions", get(handler))
 }
 
-fn add_to_library() -> Router<AppState> {
+fn revoke_session() -> Router<AppState> {
     async fn handler(
-        State(AppState { db }): State<AppState>,
-        Json(AddToLibraryData { id, name, cover }): Json<AddToLibraryData>,
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(RevokeSessionData { id }): Json<RevokeSessionData>,
     ) -> AppResult<Json<()>> {
-        db::add_to_library(
+        if !db::delete_session(&db, user.id, id).await? {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                anyhow!("Session {id} does not exist"),
+            ));
+        }
+        Ok(Json(()))
+    }
+
+    route("/revoke_session", post(handler))
+}
+
+fn get_api_tokens() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+    ) -> AppResult<Json<Vec<api_token::Model>>> {
+        let list = db::get_api_tokens(&db, user.id).await?;
+        Ok(Json(list))
+    }
+
+    route("/get_api_tokens", get(handler))
+}
+
+fn create_api_token() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(CreateApiTokenData {
+            name,
+            mut scopes,
+            expires_in_days,
+        }): Json<CreateApiTokenData>,
+    ) -> AppResult<Json<CreatedApiToken>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Token name cannot be empty"),
+            ));
+        }
+        scopes.sort();
+        scopes.dedup();
+        if scopes.is_empty() {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Token needs at least one scope"),
+            ));
+        }
+        if scopes.contains(&Scope::Admin) && Role::of(&user) < Role::Admin {
+            return Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                anyhow!("Only admins can grant the admin scope"),
+            ));
+        }
+        let expires_at = match expires_in_days {
+            Some(days @ 1..=MAX_API_TOKEN_DAYS) => Some(Utc::now() + Days::new(days)),
+            Some(_) => {
+                return Err(AppError::new(
+                    StatusCode::BAD_REQUEST,
+                    anyhow!("Expiry must be between 1 and {MAX_API_TOKEN_DAYS} days"),
+                ))
+            }
+            None => None,
+        };
+
+        let (secret, prefix) = auth::new_api_token();
+        let token = db::create_api_token(
             &db,
-            lib_comic::ActiveModel {
-                id: Set(id),
-                name: Set(name),
-                cover: Set(cover),
-                updated_at: Set(chrono::Utc::now()),
+            api_token::ActiveModel {
+                user_id: Set(user.id),
+                name: Set(name.to_string()),
+                token_hash: Set(auth::hash_token(&secret)),
+                prefix: Set(prefix),
+                scopes: Set(serde_json::to_value(&scopes)?),
+                created_at: Set(Utc::now()),
+                expires_at: Set(expires_at),
                 ..Default::default()
             },
         )
         .await?;
-        Ok(Json(()))
+        Ok(Json(CreatedApiToken { token, secret }))
     }
 
-    route("/add_to_library", post(handler))
+    route("/create_api_token", post(handler))
 }
 
-fn remove_from_library() -> Router<AppState> {
+fn revoke_api_token() -> Router<AppState> {
     async fn handler(
-        State(AppState { db }): State<AppState>,
-        Json(RemoveFromLibraryData { id }): Json<RemoveFromLibraryData>,
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(RevokeApiTokenData { id }): Json<RevokeApiTokenData>,
     ) -> AppResult<Json<()>> {
-        db::remove_from_library(&db, &id).await?;
+        if !db::delete_api_token(&db, user.id, id).await? {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                anyhow!("API token {id} does not exist"),
+            ));
+        }
         Ok(Json(()))
     }
 
-    route("/remove_from_library", post(handler))
+    route("/revoke_api_token", post(handler))
 }
 
-fn check_in_library() -> Router<AppState> {
+fn get_share_links() -> Router<AppState> {
     async fn handler(
-        State(AppState { db }): State<AppState>,
-        Query(CheckInLibraryData { id }): Query<CheckInLibraryData>,
-    ) -> AppResult<Json<CheckInLibraryResp>> {
-        let in_library = db::check_in_library(&db, &id).await?;
-        Ok(Json(CheckInLibraryResp { in_library }))
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+    ) -> AppResult<Json<Vec<share_link::Model>>> {
+        let list = db::get_share_links(&db, user.id).await?;
+        Ok(Json(list))
     }
 
-    route("/check_in_library", get(handler))
+    route("/get_share_links", get(handler))
 }
 
-fn get_comic_history() -> Router<AppState> {
+fn create_share_link() -> Router<AppState> {
     async fn handler(
-        State(AppState { db }): State<AppState>,
-        Query(GetComicHistoryData { id }): Query<GetComicHistoryData>,
-    ) -> AppResult<Json<GetComicHistoryResp>> {
-        let history = db::get_comic_history(&db, &id).await?;
-        Ok(Json(GetComicHistoryResp { history }))
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(CreateShareLinkData {
+            comic_id,
+            chapter_id,
+            expires_in_days,
+        }): Json<CreateShareLinkData>,
+    ) -> AppResult<Json<CreatedShareLink>> {
+        let days = expires_in_days.unwrap_or(DEFAULT_SHARE_LINK_DAYS);
+        if !(1..=MAX_SHARE_LINK_DAYS).contains(&days) {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Expiry must be between 1 and {MAX_SHARE_LINK_DAYS} days"),
+            ));
+        }
+
+        let site = SiteId::Manhuagui;
+        let comic = site.get_comic(comic_id.clone()).await?;
+        let chapter_name = match &chapter_id {
+            Some(chapter_id) => Some(
+                find_chapter(&comic.chapter_groups, chapter_id)
+                    .ok_or_else(|| {
+                        AppError::new(
+                            StatusCode::NOT_FOUND,
+                            anyhow!("Chapter {chapter_id} does not exist"),
+                        )
+                    })?
+                    .name
+                    .clone(),
+            ),
+            None => None,
+        };
+
+        let token = auth::new_share_token();
+        let now = Utc::now();
+        let link = db::create_share_link(
+            &db,
+            share_link::ActiveModel {
+                user_id: Set(user.id),
+                token_hash: Set(auth::hash_token(&token)),
+                comic_id: Set(comic.id.clone()),
+                comic_name: Set(comic.name.clone()),
+                chapter_id: Set(chapter_id.clone()),
+                chapter_name: Set(chapter_name),
+                created_at: Set(now),
+                expires_at: Set(now + Days::new(days)),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let path = match &chapter_id {
+            Some(chapter_id) => format!("/comic/{}/{chapter_id}?share={token}", comic.id),
+            None => format!("/comic/{}?share={token}", comic.id),
+        };
+        Ok(Json(CreatedShareLink { link, token, path }))
     }
 
-    route("/get_comic_history", get(handler))
+    route("/create_share_link", post(handler))
 }
 
-fn route(path: &str, method_router: MethodRouter<AppState>) -> Router<AppState> {
-    Router::new().route(path, method_router)
-}
+fn revoke_share_link() -> Router<AppState> {
+    async fn handler(
+        State(AppState {
+            db, shared_images, ..
+        }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(RevokeShareLinkData { id }): Json<RevokeShareLinkData>,
+    ) -> AppResult<Json<()>> {
+        if !db::delete_share_link(&db, user.id, id).await? {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                anyhow!("Share link {id} does not exist"),
+            ));
+        }
+        shared_images.forget(id);
+        Ok(Json(()))
+    }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct SearchComicQuery {
-    keyword: String,
+    route("/revoke_share_link", post(handler))
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GetComicBriefQuery {
-    id: String,
-}
+fn get_login_attempts() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Query(PaginationQuery { page, page_size }): Query<PaginationQuery>,
+        Query(LoginAttemptsQuery {
+            username,
+            ip,
+            result,
+        }): Query<LoginAttemptsQuery>,
+    ) -> AppResult<Json<PageList<login_attempt::Model>>> {
+        let res = db::get_login_attempts(
+            &db,
+            username.as_deref(),
+            ip.as_deref(),
+            result,
+            page,
+            page_size.clamp(1, MAX_PAGE_SIZE),
+        )
+        .await?;
+        Ok(Json(res))
+    }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GetChapterImagesQuery {
-    comic_id: String,
-    chapter_id: String,
+    route("/get_login_attempts", get(handler))
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct ProxyImageQuery {
-    url: String,
-}
+fn get_users() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+    ) -> AppResult<Json<Vec<user::Model>>> {
+        let list = db::get_users(&db).await?;
+        Ok(Json(list))
+    }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct CheckPasswordData {
-    password: String,
+    route("/get_users", get(handler))
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct CheckPasswordResp {
-    valid: bool,
-}
+fn create_user() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Json(CreateUserData {
+            username,
+            password,
+            role,
+        }): Json<CreateUserData>,
+    ) -> AppResult<Json<user::Model>> {
+        let username = username.trim();
+        if username.is_empty() {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Username cannot be empty"),
+            ));
+        }
+        if db::get_user_by_username(&db, username).await?.is_some() {
+            return Err(AppError::new(
+                StatusCode::CONFLICT,
+                anyhow!("User {username} already exists"),
+            ));
+        }
+        auth::validate_password(&password)?;
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct UpsertHistoryData {
-    comic_id: String,
-    chapter_id: String,
-    comic_name: String,
-    chapter_name: String,
-    page: i32,
-    visible: bool,
-}
+        let item = db::create_user(
+            &db,
+            username,
+            &auth::hash_password(&password)?,
+            role.as_str(),
+        )
+        .await?;
+        Ok(Json(item))
+    }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct DeleteHistoryData {
-    comic_id: String,
+    route("/create_user", post(handler))
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct AddToLibraryData {
-    id: String,
-    name: String,
-    cover: String,
-}
+fn delete_user() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(UserIdData { id }): Json<UserIdData>,
+    ) -> AppResult<Json<()>> {
+        if id == user.id {
+            return Err(AppError::new(
+                StatusCode::FORBIDDEN,
+                anyhow!("You cannot delete yourself"),
+            ));
+        }
+        db::delete_user(&db, id).await?;
+        Ok(Json(()))
+    }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct RemoveFromLibraryData {
-    id: String,
+    route("/delete_user", post(handler))
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct CheckInLibraryData {
-    id: String,
-}
+fn reset_user_password() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Json(ResetUserPasswordData { id, password }): Json<ResetUserPasswordData>,
+    ) -> AppResult<Json<()>> {
+        auth::validate_password(&password)?;
+        if db::get_user(&db, id).await?.is_none() {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                anyhow!("User {id} does not exist"),
+            ));
+        }
+        db::set_user_password(&db, id, &auth::hash_password(&password)?).await?;
+        db::delete_user_sessions(&db, id, None).await?;
+        Ok(Json(()))
+    }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct CheckInLibraryResp {
-    in_library: bool,
+    route("/reset_user_password", post(handler))
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GetComicHistoryData {
-    id: String,
-}
+fn upsert_history() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(UpsertHistoryData {
+            comic_id,
+            chapter_id,
+            comic_name,
+            chapter_name,
+            page,
+            total_pages,
+            visible,
+        }): Json<UpsertHistoryData>,
+    ) -> AppResult<Json<()>> {
+        let now = chrono::Utc::now();
+
+        db::upsert_history(
+            &db,
+            history::ActiveModel {
+                user_id: Set(user.id),
+                comic_id: Set(comic_id.clone()),
+                chapter_id: Set(chapter_id.clone()),
+                comic_name: Set(comic_name.clone()),
+                chapter_name: Set(chapter_name.clone()),
+                page: Set(page),
+                visible: Set(visible),
+                updated_at: Set(now),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let completed = total_pages > 0 && page >= total_pages;
+
+        db::upsert_chapter_read(
+            &db,
+            chapter_read::ActiveModel {
+                user_id: Set(user.id),
+                comic_id: Set(comic_id.clone()),
+                chapter_id: Set(chapter_id.clone()),
+                chapter_name: Set(chapter_name.clone()),
+                page: Set(page),
+                total_pages: Set(total_pages),
+                completed: Set(completed),
+                read_at: Set(now),
+                marked: Set(false),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let last_event = db::get_last_read_event(&db, user.id).await?;
+        db::add_read_event(
+            &db,
+            read_event::ActiveModel {
+                user_id: Set(user.id),
+                comic_id: Set(comic_id),
+                chapter_id: Set(chapter_id),
+                comic_name: Set(comic_name),
+                chapter_name: Set(chapter_name),
+                page: Set(page),
+                completed: Set(completed),
+                duration: Set(heartbeat_duration(last_event.as_ref(), now)),
+                created_at: Set(now),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(Json(()))
+    }
+
+    route("/upsert_history", post(handler))
+}
+
+fn get_history() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(HistoryListQuery {
+            page,
+            page_size,
+            keyword,
+        }): Query<HistoryListQuery>,
+    ) -> AppResult<Json<PageList<history::Model>>> {
+        let res = db::get_history(
+            &db,
+            user.id,
+            true,
+            keyword.as_deref(),
+            page,
+            page_size.clamp(1, MAX_PAGE_SIZE),
+        )
+        .await?;
+        Ok(Json(res))
+    }
+
+    route("/get_history", get(handler))
+}
+
+fn get_history_trash() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(HistoryListQuery {
+            page,
+            page_size,
+            keyword,
+        }): Query<HistoryListQuery>,
+    ) -> AppResult<Json<PageList<history::Model>>> {
+        let res = db::get_history(
+            &db,
+            user.id,
+            false,
+            keyword.as_deref(),
+            page,
+            page_size.clamp(1, MAX_PAGE_SIZE),
+        )
+        .await?;
+        Ok(Json(res))
+    }
+
+    route("/get_history_trash", get(handler))
+}
+
+fn restore_history() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(DeleteHistoryData { comic_id }): Json<DeleteHistoryData>,
+    ) -> AppResult<Json<()>> {
+        db::restore_history(&db, user.id, &comic_id).await?;
+        Ok(Json(()))
+    }
+
+    route("/restore_history", post(handler))
+}
+
+fn purge_history() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(DeleteHistoryData { comic_id }): Json<DeleteHistoryData>,
+    ) -> AppResult<Json<()>> {
+        db::purge_history(&db, user.id, &comic_id).await?;
+        Ok(Json(()))
+    }
+
+    route("/purge_history", post(handler))
+}
+
+fn purge_old_history() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(PurgeOldHistoryData { days }): Json<PurgeOldHistoryData>,
+    ) -> AppResult<Json<PurgeOldHistoryResp>> {
+        let before = Utc::now()
+            .checked_sub_days(Days::new(days.into()))
+            .ok_or_else(|| {
+                AppError::new(
+                    StatusCode::BAD_REQUEST,
+                    anyhow!("{days} days ago is out of range"),
+                )
+            })?;
+        let count = db::purge_history_before(&db, user.id, before).await?;
+        Ok(Json(PurgeOldHistoryResp { count }))
+    }
+
+    route("/purge_old_history", post(handler))
+}
+
+fn delete_history() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(DeleteHistoryData { comic_id }): Json<DeleteHistoryData>,
+    ) -> AppResult<Json<()>> {
+        db::delete_history(&db, user.id, &comic_id).await?;
+        Ok(Json(()))
+    }
+
+    route("/delete_history", post(handler))
+}
+
+fn get_library() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(PaginationQuery { page, page_size }): Query<PaginationQuery>,
+        Query(filter): Query<LibraryFilter>,
+    ) -> AppResult<Json<PageList<LibraryItem>>> {
+        let res = list_library(
+            &db,
+            user.id,
+            &filter,
+            page,
+            page_size.clamp(1, MAX_PAGE_SIZE),
+        )
+        .await?;
+        Ok(Json(res))
+    }
+
+    route("/get_library", get(handler))
+}
+
+fn add_to_library() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(AddToLibraryData {
+            id,
+            name,
+            cover,
+            site,
+        }): Json<AddToLibraryData>,
+    ) -> AppResult<Json<()>> {
+        db::add_to_library(
+            &db,
+            lib_comic::ActiveModel {
+                user_id: Set(user.id),
+                id: Set(id),
+                name: Set(name),
+                cover: Set(cover),
+                site: Set(site.unwrap_or(SiteId::Manhuagui).as_str().to_string()),
+                updated_at: Set(chrono::Utc::now()),
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(Json(()))
+    }
+
+    route("/add_to_library", post(handler))
+}
+
+fn remove_from_library() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(RemoveFromLibraryData { id }): Json<RemoveFromLibraryData>,
+    ) -> AppResult<Json<()>> {
+        db::remove_from_library(&db, user.id, &id).await?;
+        Ok(Json(()))
+    }
+
+    route("/remove_from_library", post(handler))
+}
+
+fn check_in_library() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(CheckInLibraryData { id }): Query<CheckInLibraryData>,
+    ) -> AppResult<Json<CheckInLibraryResp>> {
+        let in_library = db::check_in_library(&db, user.id, &id).await?;
+        Ok(Json(CheckInLibraryResp { in_library }))
+    }
+
+    route("/check_in_library", get(handler))
+}
+
+fn get_migration_candidates() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, config, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetMigrationCandidatesQuery { comic_id, sites }): Query<GetMigrationCandidatesQuery>,
+    ) -> AppResult<Json<MigrationCandidates>> {
+        let mut targets = config.enabled_sites();
+        if let Some(sites) = sites {
+            let requested = sites
+                .split(',')
+                .filter_map(SiteId::parse)
+                .collect::<Vec<_>>();
+            targets.retain(|site| requested.contains(site));
+        }
+
+        let res = migrate::find_candidates(&db, user.id, &comic_id, &targets).await?;
+        Ok(Json(res))
+    }
+
+    route("/get_migration_candidates", get(handler))
+}
+
+fn migrate_comic() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, config, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(MigrateComicData {
+            comic_id,
+            target_site,
+            target_comic_id,
+        }): Json<MigrateComicData>,
+    ) -> AppResult<Json<MigrationResult>> {
+        if !config.enabled_sites().contains(&target_site) {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Site {} is not enabled", target_site.as_str()),
+            ));
+        }
+
+        let res =
+            migrate::migrate_comic(&db, user.id, &comic_id, target_site, &target_comic_id).await?;
+        Ok(Json(res))
+    }
+
+    route("/migrate_comic", post(handler))
+}
+
+fn get_collections() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+    ) -> AppResult<Json<Vec<CollectionItem>>> {
+        let list = db::get_collections(&db, user.id)
+            .await?
+            .into_iter()
+            .map(|(collection, comic_count)| CollectionItem {
+                collection,
+                comic_count,
+            })
+            .collect();
+        Ok(Json(list))
+    }
+
+    route("/get_collections", get(handler))
+}
+
+fn create_collection() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(CreateCollectionData { name }): Json<CreateCollectionData>,
+    ) -> AppResult<Json<collection::Model>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Collection name cannot be empty"),
+            ));
+        }
+        let item = db::create_collection(&db, user.id, name).await?;
+        Ok(Json(item))
+    }
+
+    route("/create_collection", post(handler))
+}
+
+fn rename_collection() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(RenameCollectionData { id, name }): Json<RenameCollectionData>,
+    ) -> AppResult<Json<()>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Collection name cannot be empty"),
+            ));
+        }
+        db::rename_collection(&db, user.id, id, name).await?;
+        Ok(Json(()))
+    }
+
+    route("/rename_collection", post(handler))
+}
+
+fn delete_collection() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(DeleteCollectionData { id }): Json<DeleteCollectionData>,
+    ) -> AppResult<Json<()>> {
+        db::delete_collection(&db, user.id, id).await?;
+        Ok(Json(()))
+    }
+
+    route("/delete_collection", post(handler))
+}
+
+fn reorder_collections() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(ReorderCollectionsData { ids }): Json<ReorderCollectionsData>,
+    ) -> AppResult<Json<()>> {
+        db::reorder_collections(&db, user.id, &ids).await?;
+        Ok(Json(()))
+    }
+
+    route("/reorder_collections", post(handler))
+}
+
+fn get_comic_collections() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetComicHistoryData { id }): Query<GetComicHistoryData>,
+    ) -> AppResult<Json<Vec<i32>>> {
+        let ids = db::get_comic_collection_ids(&db, user.id, &id).await?;
+        Ok(Json(ids))
+    }
+
+    route("/get_comic_collections", get(handler))
+}
+
+fn set_comic_collections() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(SetComicCollectionsData {
+            comic_id,
+            collection_ids,
+        }): Json<SetComicCollectionsData>,
+    ) -> AppResult<Json<()>> {
+        if !db::check_in_library(&db, user.id, &comic_id).await? {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                anyhow!("Comic {comic_id} is not in the library"),
+            ));
+        }
+        db::set_comic_collections(&db, user.id, &comic_id, &collection_ids).await?;
+        Ok(Json(()))
+    }
+
+    route("/set_comic_collections", post(handler))
+}
+
+fn reorder_collection_comics() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(ReorderCollectionComicsData {
+            collection_id,
+            comic_ids,
+        }): Json<ReorderCollectionComicsData>,
+    ) -> AppResult<Json<()>> {
+        db::reorder_collection_comics(&db, user.id, collection_id, &comic_ids).await?;
+        Ok(Json(()))
+    }
+
+    route("/reorder_collection_comics", post(handler))
+}
+
+fn get_comic_note() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetComicHistoryData { id }): Query<GetComicHistoryData>,
+    ) -> AppResult<Json<GetComicNoteResp>> {
+        let note = db::get_comic_note(&db, user.id, &id).await?;
+        let tags = db::get_comic_tags(&db, user.id, vec![id])
+            .await?
+            .into_iter()
+            .map(|item| item.tag)
+            .collect();
+        Ok(Json(GetComicNoteResp { note, tags }))
+    }
+
+    route("/get_comic_note", get(handler))
+}
+
+fn upsert_comic_note() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(UpsertComicNoteData {
+            comic_id,
+            rating,
+            notes,
+        }): Json<UpsertComicNoteData>,
+    ) -> AppResult<Json<()>> {
+        if rating.is_some_and(|rating| !(1..=10).contains(&rating)) {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Rating must be between 1 and 10"),
+            ));
+        }
+        if !db::check_in_library(&db, user.id, &comic_id).await? {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                anyhow!("Comic {comic_id} is not in the library"),
+            ));
+        }
+
+        let now = chrono::Utc::now();
+        db::upsert_comic_note(
+            &db,
+            comic_note::ActiveModel {
+                user_id: Set(user.id),
+                comic_id: Set(comic_id),
+                rating: Set(rating),
+                notes: Set(notes),
+                created_at: Set(now),
+                updated_at: Set(now),
+            },
+        )
+        .await?;
+        Ok(Json(()))
+    }
+
+    route("/upsert_comic_note", post(handler))
+}
+
+fn delete_comic_note() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(DeleteHistoryData { comic_id }): Json<DeleteHistoryData>,
+    ) -> AppResult<Json<()>> {
+        db::delete_comic_note(&db, user.id, &comic_id).await?;
+        Ok(Json(()))
+    }
+
+    route("/delete_comic_note", post(handler))
+}
+
+fn set_comic_tags() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(SetComicTagsData { comic_id, tags }): Json<SetComicTagsData>,
+    ) -> AppResult<Json<()>> {
+        if !db::check_in_library(&db, user.id, &comic_id).await? {
+            return Err(AppError::new(
+                StatusCode::NOT_FOUND,
+                anyhow!("Comic {comic_id} is not in the library"),
+            ));
+        }
+
+        let mut tags = tags
+            .iter()
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+
+        db::set_comic_tags(&db, user.id, &comic_id, &tags).await?;
+        Ok(Json(()))
+    }
+
+    route("/set_comic_tags", post(handler))
+}
+
+fn get_tags() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+    ) -> AppResult<Json<Vec<TagItem>>> {
+        let list = db::get_all_tags(&db, user.id)
+            .await?
+            .into_iter()
+            .map(|(tag, count)| TagItem { tag, count })
+            .collect();
+        Ok(Json(list))
+    }
+
+    route("/get_tags", get(handler))
+}
+
+fn get_comic_history() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetComicHistoryData { id }): Query<GetComicHistoryData>,
+    ) -> AppResult<Json<GetComicHistoryResp>> {
+        let history = db::get_comic_history(&db, user.id, &id).await?;
+        Ok(Json(GetComicHistoryResp { history }))
+    }
+
+    route("/get_comic_history", get(handler))
+}
+
+fn get_next_unread_chapter() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetNextUnreadChapterQuery { comic_id }): Query<GetNextUnreadChapterQuery>,
+    ) -> AppResult<Json<GetNextUnreadChapterResp>> {
+        let site = SiteId::Manhuagui;
+        let comic = site.get_comic(comic_id.clone()).await?;
+        let history = db::get_comic_history(&db, user.id, &comic_id).await?;
+
+        let last_read_id = history.as_ref().map(|item| item.chapter_id.as_str());
+
+        // resume the latest chapter if it was left unfinished
+        let mut chapter = None;
+        if let Some(last_read_id) = last_read_id {
+            let progress = db::get_chapter_read(&db, user.id, &comic_id, last_read_id).await?;
+            if progress.is_some_and(|progress| !progress.completed) {
+                chapter = find_chapter(&comic.chapter_groups, last_read_id);
+            }
+        }
+        let chapter = chapter
+            .or_else(|| next_unread(&comic.chapter_groups, last_read_id))
+            .cloned();
+
+        Ok(Json(GetNextUnreadChapterResp { chapter }))
+    }
+
+    route("/get_next_unread_chapter", get(handler))
+}
+
+fn get_read_chapters() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetReadChaptersQuery { comic_id }): Query<GetReadChaptersQuery>,
+    ) -> AppResult<Json<Vec<chapter_read::Model>>> {
+        let list = db::get_chapter_reads(&db, user.id, &comic_id).await?;
+        Ok(Json(list))
+    }
+
+    route("/get_read_chapters", get(handler))
+}
+
+fn get_chapter_progress() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetChapterImagesQuery {
+            comic_id,
+            chapter_id,
+        }): Query<GetChapterImagesQuery>,
+    ) -> AppResult<Json<GetChapterProgressResp>> {
+        let progress = db::get_chapter_read(&db, user.id, &comic_id, &chapter_id).await?;
+        Ok(Json(GetChapterProgressResp { progress }))
+    }
+
+    route("/get_chapter_progress", get(handler))
+}
+
+fn mark_chapters() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Json(MarkChaptersData {
+            comic_id,
+            read,
+            scope,
+        }): Json<MarkChaptersData>,
+    ) -> AppResult<Json<MarkChaptersResp>> {
+        let site = SiteId::Manhuagui;
+        let comic = site.get_comic(comic_id.clone()).await?;
+        let chapters = select_chapters(&comic.chapter_groups, &scope)?;
+        let count = chapters.len();
+
+        if read {
+            let now = chrono::Utc::now();
+            let items = chapters
+                .into_iter()
+                .map(|chapter| chapter_read::ActiveModel {
+                    user_id: Set(user.id),
+                    comic_id: Set(comic_id.clone()),
+                    chapter_id: Set(chapter.id.clone()),
+                    chapter_name: Set(chapter.name.clone()),
+                    page: Set(0),
+                    total_pages: Set(0),
+                    completed: Set(true),
+                    read_at: Set(now),
+                    marked: Set(true),
+                    ..Default::default()
+                })
+                .collect();
+            db::mark_chapters_read(&db, items).await?;
+        } else {
+            let chapter_ids = chapters
+                .into_iter()
+                .map(|chapter| chapter.id.clone())
+                .collect();
+            db::mark_chapters_unread(&db, user.id, &comic_id, chapter_ids).await?;
+        }
+
+        Ok(Json(MarkChaptersResp { count }))
+    }
+
+    route("/mark_chapters", post(handler))
+}
+
+fn get_reading_stats() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(GetReadingStatsQuery {
+            from,
+            to,
+            tz_offset,
+        }): Query<GetReadingStatsQuery>,
+    ) -> AppResult<Json<ReadingStats>> {
+        let offset = tz_offset
+            .checked_mul(60)
+            .and_then(FixedOffset::east_opt)
+            .ok_or_else(|| {
+                AppError::new(
+                    StatusCode::BAD_REQUEST,
+                    anyhow!("Invalid timezone offset {tz_offset}"),
+                )
+            })?;
+        let to = to.unwrap_or_else(|| Utc::now().with_timezone(&offset).date_naive());
+        let from = match from {
+            Some(from) => from,
+            None => to.checked_sub_days(Days::new(29)).ok_or_else(|| {
+                AppError::new(StatusCode::BAD_REQUEST, anyhow!("Invalid end date {to}"))
+            })?,
+        };
+        if from > to || (to - from).num_days() > 366 {
+            return Err(AppError::new(
+                StatusCode::BAD_REQUEST,
+                anyhow!("Date range must be within 366 days"),
+            ));
+        }
+
+        let start = from
+            .and_hms_opt(0, 0, 0)
+            .and_then(|time| time.and_local_timezone(offset).single())
+            .ok_or_else(|| {
+                AppError::new(
+                    StatusCode::BAD_REQUEST,
+                    anyhow!("Invalid start date {from}"),
+                )
+            })?
+            .to_utc();
+        let end = start
+            .checked_add_days(Days::new((to - from).num_days() as u64 + 1))
+            .ok_or_else(|| {
+                AppError::new(StatusCode::BAD_REQUEST, anyhow!("Invalid end date {to}"))
+            })?;
+
+        let events = db::get_read_events(&db, user.id, start, end).await?;
+        let chapter_reads = db::get_chapter_reads_between(&db, user.id, start, end).await?;
+        let mut comic_ids = events
+            .iter()
+            .map(|event| event.comic_id.clone())
+            .collect::<Vec<_>>();
+        comic_ids.sort();
+        comic_ids.dedup();
+        let metas = db::get_comic_metas(&db, comic_ids).await?;
+
+        let stats = compute_stats(from, to, offset, &events, &chapter_reads, &metas);
+        Ok(Json(stats))
+    }
+
+    route("/get_reading_stats", get(handler))
+}
+
+fn export_backup() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+    ) -> AppResult<impl IntoResponse> {
+        let backup = backup::export_backup(&db, user.id).await?;
+        let filename = format!(
+            "comiya-backup-{}.json",
+            backup.exported_at.format("%Y%m%d-%H%M%S")
+        );
+
+        Ok((
+            [(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            )],
+            Json(backup),
+        ))
+    }
+
+    route("/export_backup", get(handler))
+}
+
+fn import_backup() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        Query(ImportBackupQuery { strategy }): Query<ImportBackupQuery>,
+        Json(backup): Json<Backup>,
+    ) -> AppResult<Json<ImportSummary>> {
+        let summary = backup::import_backup(&db, user.id, backup, strategy).await?;
+        Ok(Json(summary))
+    }
+
+    // archives of large libraries easily exceed the default 2MB limit
+    route("/import_backup", post(handler)).layer(DefaultBodyLimit::max(64 * 1024 * 1024))
+}
+
+fn import_tachiyomi_backup() -> Router<AppState> {
+    async fn handler(
+        State(AppState { db, .. }): State<AppState>,
+        Extension(user): Extension<user::Model>,
+        body: Bytes,
+    ) -> AppResult<Json<TachiyomiImportSummary>> {
+        let backup = tachiyomi::decode_backup(&body)
+            .map_err(|err| AppError::new(StatusCode::BAD_REQUEST, err))?;
+        let summary = tachiyomi::import_tachiyomi_backup(&db, user.id, backup).await?;
+        Ok(Json(summary))
+    }
+
+    route("/import_tachiyomi_backup", post(handler)).layer(DefaultBodyLimit::max(64 * 1024 * 1024))
+}
+
+fn route(path: &str, method_router: MethodRouter<AppState>) -> Router<AppState> {
+    Router::new().route(path, method_router)
+}
+
+/// The site a request is for, the first enabled one when not given.
+fn enabled_site(config: &Config, site: Option<&str>) -> AppResult<SiteId> {
+    let enabled = config.enabled_sites();
+    let site = match site {
+        Some(site) => SiteId::parse(site)
+            .filter(|site| enabled.contains(site))
+            .ok_or_else(|| anyhow!("Site {site} is not enabled")),
+        None => enabled
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("No site is enabled")),
+    };
+    site.map_err(|err| AppError::new(StatusCode::BAD_REQUEST, err))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HealthStatus {
+    status: &'static str,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetSiteStatusQuery {
+    check: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SiteStatusItem {
+    site: SiteId,
+    #[serde(flatten)]
+    health: SiteHealth,
+    check: Option<SiteStatus>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SiteQuery {
+    /// The first enabled site when not given.
+    site: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchComicQuery {
+    keyword: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchAllSitesQuery {
+    keyword: String,
+    /// Comma separated site ids, limited to the enabled ones.
+    sites: Option<String>,
+    /// Capped at [`MAX_SITE_TIMEOUT`].
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageQuery {
+    #[serde(default = "default_page")]
+    page: u32,
+}
+
+fn default_page() -> u32 {
+    1
+}
+
+fn default_page_size() -> u32 {
+    20
+}
+
+/// Upper bound on the `pageSize` of every paginated endpoint.
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PaginationQuery {
+    #[serde(default = "default_page")]
+    page: u32,
+    #[serde(default = "default_page_size")]
+    page_size: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HistoryListQuery {
+    #[serde(default = "default_page")]
+    page: u32,
+    #[serde(default = "default_page_size")]
+    page_size: u32,
+    keyword: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetRankComicsQuery {
+    rank_type: RankType,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetComicBriefQuery {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetChapterImagesQuery {
+    comic_id: String,
+    chapter_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProxyImageQuery {
+    url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginData {
+    username: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChangePasswordData {
+    current_password: String,
+    new_password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateUserData {
+    username: String,
+    password: String,
+    #[serde(default = "default_role")]
+    role: Role,
+}
+
+fn default_role() -> Role {
+    Role::Member
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserIdData {
+    id: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginAttemptsQuery {
+    username: Option<String>,
+    ip: Option<String>,
+    result: Option<LoginResult>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionItem {
+    #[serde(flatten)]
+    session: session::Model,
+    /// Whether this is the session making the request.
+    current: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevokeSessionData {
+    id: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateApiTokenData {
+    name: String,
+    scopes: Vec<Scope>,
+    /// Never expires when missing.
+    expires_in_days: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatedApiToken {
+    #[serde(flatten)]
+    token: api_token::Model,
+    /// Only returned here, since just its hash is stored.
+    secret: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevokeApiTokenData {
+    id: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateShareLinkData {
+    comic_id: String,
+    /// The whole comic is shared when missing.
+    chapter_id: Option<String>,
+    expires_in_days: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreatedShareLink {
+    #[serde(flatten)]
+    link: share_link::Model,
+    /// Only returned here, since just its hash is stored.
+    token: String,
+    /// Where the link opens in the web app.
+    path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevokeShareLinkData {
+    id: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResetUserPasswordData {
+    id: i32,
+    password: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpsertHistoryData {
+    comic_id: String,
+    chapter_id: String,
+    comic_name: String,
+    chapter_name: String,
+    page: i32,
+    /// Page count of the chapter, `0` when unknown.
+    #[serde(default)]
+    total_pages: i32,
+    visible: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteHistoryData {
+    comic_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PurgeOldHistoryData {
+    days: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PurgeOldHistoryResp {
+    count: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddToLibraryData {
+    id: String,
+    name: String,
+    cover: String,
+    site: Option<SiteId>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoveFromLibraryData {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckInLibraryData {
+    id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckInLibraryResp {
+    in_library: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CollectionItem {
+    #[serde(flatten)]
+    collection: collection::Model,
+    comic_count: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateCollectionData {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RenameCollectionData {
+    id: i32,
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeleteCollectionData {
+    id: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReorderCollectionsData {
+    ids: Vec<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetComicCollectionsData {
+    comic_id: String,
+    collection_ids: Vec<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReorderCollectionComicsData {
+    collection_id: i32,
+    comic_ids: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetComicNoteResp {
+    note: Option<comic_note::Model>,
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpsertComicNoteData {
+    comic_id: String,
+    rating: Option<i32>,
+    #[serde(default)]
+    notes: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetComicTagsData {
+    comic_id: String,
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TagItem {
+    tag: String,
+    count: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetComicHistoryData {
+    id: String,
+}
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GetComicHistoryResp {
     history: Option<history::Model>,
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetNextUnreadChapterQuery {
+    comic_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetNextUnreadChapterResp {
+    chapter: Option<ComicChapterBrief>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetReadChaptersQuery {
+    comic_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetChapterProgressResp {
+    progress: Option<chapter_read::Model>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkChaptersData {
+    comic_id: String,
+    read: bool,
+    #[serde(flatten)]
+    scope: ChapterScope,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkChaptersResp {
+    count: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetReadingStatsQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    /// Minutes east of UTC used for day boundaries.
+    #[serde(default)]
+    tz_offset: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetMigrationCandidatesQuery {
+    comic_id: String,
+    /// Comma separated site ids, limited to the enabled ones.
+    sites: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MigrateComicData {
+    comic_id: String,
+    target_site: SiteId,
+    target_comic_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportBackupQuery {
+    #[serde(default)]
+    strategy: MergeStrategy,
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use anyhow::anyhow;
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::{request::Parts, Uri},
+};
+use entity::share_link;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::{auth::hash_token, db};
+
+use super::{types::AppError, AppState};
+
+pub const DEFAULT_SHARE_LINK_DAYS: u64 = 7;
+
+pub const MAX_SHARE_LINK_DAYS: u64 = 90;
+
+/// Images handed out through share links. Images are proxied by URL, so
+/// links may only fetch the ones of chapters they were used to open, rather
+/// than anything the proxy can reach.
+pub struct SharedImages {
+    map: Mutex<HashMap<i32, HashSet<String>>>,
+    /// Images remembered per link, from `cache.shared_images`.
+    capacity: usize,
+}
+
+impl SharedImages {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            map: Mutex::default(),
+            capacity,
+        }
+    }
+
+    pub fn allow(&self, link_id: i32, images: &[String]) {
+        let mut map = self.map.lock().unwrap();
+        let allowed = map.entry(link_id).or_default();
+        if allowed.len() + images.len() > self.capacity {
+            allowed.clear();
+        }
+        allowed.extend(images.iter().cloned());
+    }
+
+    pub fn contains(&self, link_id: i32, url: &str) -> bool {
+        self.map
+            .lock()
+            .unwrap()
+            .get(&link_id)
+            .is_some_and(|allowed| allowed.contains(url))
+    }
+
+    pub fn forget(&self, link_id: i32) {
+        self.map.lock().unwrap().remove(&link_id);
+    }
+}
+
+/// The share link a request was let in by, if any.
+pub struct ShareAccess(pub Option<share_link::Model>);
+
+impl FromRequestParts<AppState> for ShareAccess {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _: &AppState) -> Result<Self, AppError> {
+        Ok(ShareAccess(parts.extensions.get().cloned()))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShareQuery {
+    share: Option<String>,
+    id: Option<String>,
+    comic_id: Option<String>,
+    chapter_id: Option<String>,
+    url: Option<String>,
+}
+
+/// Resolves the `share` query parameter, for requests without an account.
+/// Links only open `get_comic`, `get_chapter` and `proxy_image` for the comic
+/// or chapter they were created for.
+pub async fn resolve_share_link(
+    state: &AppState,
+    uri: &Uri,
+) -> Result<Option<share_link::Model>, AppError> {
+    let Ok(Query(query)) = Query::<ShareQuery>::try_from_uri(uri) else {
+        return Ok(None);
+    };
+    let Some(token) = &query.share else {
+        return Ok(None);
+    };
+    let Some(link) = db::get_share_link_by_hash(&state.db, &hash_token(token)).await? else {
+        return Err(AppError::new(
+            StatusCode::UNAUTHORIZED,
+            anyhow!("Share link is invalid or has expired"),
+        ));
+    };
+
+    let comic_matches = |comic_id: &Option<String>| comic_id.as_ref() == Some(&link.comic_id);
+    let allowed = match uri.path() {
+        "/get_comic" => link.chapter_id.is_none() && comic_matches(&query.id),
+        "/get_chapter" => {
+            comic_matches(&query.comic_id)
+                && query.chapter_id.as_ref().is_some_and(|chapter_id| {
+                    link.chapter_id.as_ref().is_none_or(|id| id == chapter_id)
+                })
+        }
+        "/proxy_image" => query
+            .url
+            .as_ref()
+            .is_some_and(|url| state.shared_images.contains(link.id, url)),
+        _ => false,
+    };
+    if !allowed {
+        return Err(AppError::new(
+            StatusCode::FORBIDDEN,
+            anyhow!("Share link does not cover this"),
+        ));
+    }
+    Ok(Some(link))
+}
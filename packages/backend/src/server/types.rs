@@ -15,15 +15,26 @@ where
     pub data: Option<T>,
 }
 
-pub struct AppError(anyhow::Error);
+pub struct AppError {
+    status: StatusCode,
+    error: anyhow::Error,
+}
+
+impl AppError {
+    /// Errors converted with `?` are reported as internal errors, this is for
+    /// the ones clients are expected to handle.
+    pub fn new(status: StatusCode, error: anyhow::Error) -> Self {
+        Self { status, error }
+    }
+}
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
+            self.status,
             Json(Resp::<()> {
                 code: -1,
-                msg: self.0.to_string(),
+                msg: self.error.to_string(),
                 data: None,
             }),
         )
@@ -36,7 +47,7 @@ where
     E: Into<anyhow::Error>,
 {
     fn from(value: E) -> Self {
-        Self(value.into())
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, value.into())
     }
 }
 
@@ -1,8 +1,41 @@
-use anyhow::Result;
+use std::{collections::HashMap, future::Future, sync::OnceLock, time::Duration};
+
+use anyhow::{anyhow, Result};
 use dom_query::{Document, Selection};
 use regex::Regex;
+use reqwest::IntoUrl;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    chapter::{parse_chapter_number, sort_chapter_groups, ChapterKind},
+    check,
+    config::HttpConfig,
+};
+
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Sets up the client sites and images are fetched with, before the first
+/// request.
+pub fn init_http_client(config: &HttpConfig) -> Result<()> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs));
+    if let Some(user_agent) = &config.user_agent {
+        builder = builder.user_agent(user_agent);
+    }
+    HTTP_CLIENT
+        .set(builder.build()?)
+        .map_err(|_| anyhow!("HTTP client is already set up"))
+}
+
+pub fn http_client() -> &'static reqwest::Client {
+    HTTP_CLIENT.get_or_init(reqwest::Client::new)
+}
+
+async fn fetch(url: impl IntoUrl) -> reqwest::Result<reqwest::Response> {
+    http_client().get(url).send().await
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ComicBrief {
@@ -14,12 +47,14 @@ pub struct ComicBrief {
     pub pub_date: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ComicChapterBrief {
     pub id: String,
     pub comic_id: String,
     pub name: String,
+    pub kind: ChapterKind,
+    pub number: Option<f64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,6 +64,35 @@ pub struct ComicChapterGroup {
     pub chapters: Vec<ComicChapterBrief>,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ComicStatus {
+    Ongoing,
+    Completed,
+    #[default]
+    Unknown,
+}
+
+impl ComicStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComicStatus::Ongoing => "ongoing",
+            ComicStatus::Completed => "completed",
+            ComicStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl From<&str> for ComicStatus {
+    fn from(value: &str) -> Self {
+        match value {
+            "ongoing" => ComicStatus::Ongoing,
+            "completed" => ComicStatus::Completed,
+            _ => ComicStatus::Unknown,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Comic {
@@ -38,6 +102,15 @@ pub struct Comic {
     pub author: Vec<String>,
     pub intro: String,
     pub pub_date: String,
+    pub status: ComicStatus,
+    pub genres: Vec<String>,
+    pub region: String,
+    pub aliases: Vec<String>,
+    /// Upstream rating on a 0-10 scale, if anyone has voted.
+    pub rating: Option<f64>,
+    pub last_chapter_name: String,
+    /// Date of the latest upstream update as `YYYY-MM-DD`.
+    pub last_update: String,
     pub chapter_groups: Vec<ComicChapterGroup>,
     pub first_chapter_id: String,
 }
@@ -54,22 +127,225 @@ pub struct ComicChapter {
     pub images: Vec<String>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageList<T> {
+    pub list: Vec<T>,
+    pub page: u32,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RankType {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BrowseOrder {
+    Index,
+    #[default]
+    Update,
+    View,
+    Rate,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseFilter {
+    pub genre: Option<String>,
+    pub region: Option<String>,
+    pub status: Option<String>,
+    pub letter: Option<String>,
+    #[serde(default)]
+    pub order: BrowseOrder,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseFilterOption {
+    pub value: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseFilterGroup {
+    /// Field name of [`BrowseFilter`] the options apply to.
+    pub key: String,
+    pub name: String,
+    pub options: Vec<BrowseFilterOption>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComicComment {
+    pub id: String,
+    pub user_name: String,
+    pub avatar: String,
+    pub content: String,
+    pub created_at: String,
+    pub like_count: u32,
+    pub reply_count: u32,
+}
+
 #[trait_variant::make(Send)]
 pub trait Site {
     async fn search_comic(&self, keyword: String) -> Result<Vec<ComicBrief>>;
     async fn get_comic(&self, id: String) -> Result<Comic>;
     async fn get_chapter(&self, comic_id: String, chapter_id: String) -> Result<ComicChapter>;
+
+    // `trait_variant` does not desugar default bodies, so optional methods
+    // spell out the returned future themselves
+    fn get_latest(&self, _page: u32) -> impl Future<Output = Result<PageList<ComicBrief>>> + Send {
+        async { Err(anyhow!("Latest updates are not supported by this site")) }
+    }
+
+    fn get_rank(
+        &self,
+        _rank_type: RankType,
+    ) -> impl Future<Output = Result<Vec<ComicBrief>>> + Send {
+        async { Err(anyhow!("Rankings are not supported by this site")) }
+    }
+
+    fn browse(
+        &self,
+        _filter: BrowseFilter,
+        _page: u32,
+    ) -> impl Future<Output = Result<PageList<ComicBrief>>> + Send {
+        async { Err(anyhow!("Browsing is not supported by this site")) }
+    }
+
+    fn browse_filters(&self) -> Vec<BrowseFilterGroup> {
+        vec![]
+    }
+
+    fn get_related(&self, _id: String) -> impl Future<Output = Result<Vec<ComicBrief>>> + Send {
+        async { Err(anyhow!("Related comics are not supported by this site")) }
+    }
+
+    fn get_comments(
+        &self,
+        _id: String,
+        _page: u32,
+    ) -> impl Future<Output = Result<PageList<ComicComment>>> + Send {
+        async { Err(anyhow!("Comments are not supported by this site")) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SiteId {
+    Manhuagui,
+}
+
+impl SiteId {
+    pub const ALL: [SiteId; 1] = [SiteId::Manhuagui];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SiteId::Manhuagui => "manhuagui",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|site| site.as_str() == value.trim())
+    }
+
+    pub async fn search_comic(&self, keyword: String) -> Result<Vec<ComicBrief>> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.search_comic(keyword).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    pub async fn get_comic(&self, id: String) -> Result<Comic> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.get_comic(id).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    pub async fn get_chapter(&self, comic_id: String, chapter_id: String) -> Result<ComicChapter> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.get_chapter(comic_id, chapter_id).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    pub async fn get_latest(&self, page: u32) -> Result<PageList<ComicBrief>> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.get_latest(page).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    pub async fn get_rank(&self, rank_type: RankType) -> Result<Vec<ComicBrief>> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.get_rank(rank_type).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    pub async fn browse(&self, filter: BrowseFilter, page: u32) -> Result<PageList<ComicBrief>> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.browse(filter, page).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    pub fn browse_filters(&self) -> Vec<BrowseFilterGroup> {
+        match self {
+            SiteId::Manhuagui => Manhuagui.browse_filters(),
+        }
+    }
+
+    pub async fn get_related(&self, id: String) -> Result<Vec<ComicBrief>> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.get_related(id).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    pub async fn get_comments(&self, id: String, page: u32) -> Result<PageList<ComicComment>> {
+        let res = match self {
+            SiteId::Manhuagui => Manhuagui.get_comments(id, page).await,
+        };
+        check::record(*self, &res);
+        res
+    }
+
+    /// Sent along with image requests, which are refused without it.
+    pub fn referer(&self) -> &'static str {
+        match self {
+            SiteId::Manhuagui => "https://www.manhuagui.com/",
+        }
+    }
 }
 
 pub struct Manhuagui;
 
 impl Site for Manhuagui {
     async fn get_comic(&self, id: String) -> Result<Comic> {
-        let body = reqwest::get(format!("https://www.manhuagui.com/comic/{id}"))
+        let body = fetch(format!("https://www.manhuagui.com/comic/{id}"))
             .await?
             .text()
             .await?;
 
+        let rating = get_manhuagui_rating(&id).await.unwrap_or(None);
+
         let mut doc = Document::from(body);
 
         let brief = parse_brief(
@@ -84,6 +360,8 @@ impl Site for Manhuagui {
             id.clone(),
         );
 
+        let detail = parse_manhuagui_detail(&doc.select(".book-detail>.detail-list"));
+
         let first_chapter_id = doc
             .select_single(".book-btn a")
             .attr_or("href", "")
@@ -106,7 +384,7 @@ impl Site for Manhuagui {
             }
         }
 
-        let chapter_groups = doc
+        let mut chapter_groups = doc
             .select("h4:has(~ .chapter-list")
             .iter()
             .enumerate()
@@ -116,7 +394,7 @@ impl Site for Manhuagui {
                 let chapters = doc
                     .select(&format!("h4:nth-of-type({}) ~ .chapter-list ul", index + 1))
                     .iter()
-                    .map(|ul_node| {
+                    .flat_map(|ul_node| {
                         ul_node
                             .select("li a")
                             .iter()
@@ -132,15 +410,17 @@ impl Site for Manhuagui {
                                 //     a_node.attr_or("href", "")
                                 // );
                                 let name = a_node.attr_or("title", "").to_string();
+                                let number = parse_chapter_number(&name, &group_name);
                                 ComicChapterBrief {
                                     id: chapter_id,
                                     comic_id: id.clone(),
                                     name,
+                                    kind: number.kind,
+                                    number: number.number,
                                 }
                             })
                             .rev()
                     })
-                    .flatten()
                     .collect();
 
                 ComicChapterGroup {
@@ -148,7 +428,8 @@ impl Site for Manhuagui {
                     chapters,
                 }
             })
-            .collect();
+            .collect::<Vec<_>>();
+        sort_chapter_groups(&mut chapter_groups);
 
         Ok(Comic {
             id: brief.id,
@@ -157,6 +438,13 @@ impl Site for Manhuagui {
             author: brief.author,
             pub_date: brief.pub_date,
             intro: brief.intro,
+            status: detail.status,
+            genres: detail.genres,
+            region: detail.region,
+            aliases: detail.aliases,
+            rating,
+            last_chapter_name: detail.last_chapter_name,
+            last_update: detail.last_update,
             chapter_groups,
             first_chapter_id,
         })
@@ -164,7 +452,7 @@ impl Site for Manhuagui {
 
     async fn search_comic(&self, keyword: String) -> Result<Vec<ComicBrief>> {
         let encoded = urlencoding::encode(&keyword);
-        let body = reqwest::get(format!("https://www.manhuagui.com/s/{}.html", encoded))
+        let body = fetch(format!("https://www.manhuagui.com/s/{}.html", encoded))
             .await?
             .text()
             .await?;
@@ -204,7 +492,7 @@ impl Site for Manhuagui {
     }
 
     async fn get_chapter(&self, comic_id: String, chapter_id: String) -> Result<ComicChapter> {
-        let body = reqwest::get(format!(
+        let body = fetch(format!(
             "https://www.manhuagui.com/comic/{comic_id}/{chapter_id}.html"
         ))
         .await?
@@ -323,6 +611,422 @@ impl Site for Manhuagui {
             images: list,
         })
     }
+
+    async fn get_latest(&self, page: u32) -> Result<PageList<ComicBrief>> {
+        self.browse(BrowseFilter::default(), page).await
+    }
+
+    async fn get_rank(&self, rank_type: RankType) -> Result<Vec<ComicBrief>> {
+        let path = match rank_type {
+            RankType::Day => "",
+            RankType::Week => "week.html",
+            RankType::Month => "month.html",
+        };
+        let body = fetch(format!("https://www.manhuagui.com/rank/{path}"))
+            .await?
+            .text()
+            .await?;
+
+        let doc = Document::from(body);
+
+        let list = doc
+            .select(".rank-detail tr:has(.rank-title)")
+            .iter()
+            .map(|row| {
+                let link = row.select_single(".rank-title a");
+                let id = link
+                    .attr_or("href", "")
+                    .trim()
+                    .trim_start_matches("/comic/")
+                    .trim_end_matches("/")
+                    .to_string();
+                let author = row
+                    .select(".rank-author a")
+                    .iter()
+                    .map(|item| item.text().trim().to_string())
+                    .collect();
+
+                ComicBrief {
+                    cover: manhuagui_cover(&id),
+                    id,
+                    name: link.text().trim().to_string(),
+                    author,
+                    intro: "".to_string(),
+                    pub_date: "".to_string(),
+                }
+            })
+            .collect();
+
+        Ok(list)
+    }
+
+    async fn browse(&self, filter: BrowseFilter, page: u32) -> Result<PageList<ComicBrief>> {
+        let page = page.max(1);
+
+        // manhuagui joins the selected filters with `_` in the fixed order of
+        // region, genre, letter and status, e.g. `/list/japan_rexue_lianzai/`
+        let filters = [
+            &filter.region,
+            &filter.genre,
+            &filter.letter,
+            &filter.status,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+        let order = match filter.order {
+            BrowseOrder::Index => "index",
+            BrowseOrder::Update => "update",
+            BrowseOrder::View => "view",
+            BrowseOrder::Rate => "rate",
+        };
+        let url = if filters.is_empty() {
+            format!("https://www.manhuagui.com/list/{order}_p{page}.html")
+        } else {
+            format!("https://www.manhuagui.com/list/{filters}/{order}_p{page}.html")
+        };
+
+        let body = fetch(url).await?.text().await?;
+
+        let doc = Document::from(body);
+
+        let list = doc
+            .select("#contList>li")
+            .iter()
+            .map(|item| parse_manhuagui_list_item(&item))
+            .collect();
+
+        let has_more = doc.select("#AspNetPager1 a.next").exists();
+
+        Ok(PageList {
+            list,
+            page,
+            has_more,
+        })
+    }
+
+    async fn get_related(&self, id: String) -> Result<Vec<ComicBrief>> {
+        let body = fetch(format!("https://www.manhuagui.com/comic/{id}"))
+            .await?
+            .text()
+            .await?;
+
+        let doc = Document::from(body);
+
+        let list = doc
+            .select(".side-box .side-pic>li")
+            .iter()
+            .map(|item| parse_manhuagui_list_item(&item))
+            .filter(|brief| !brief.id.is_empty() && brief.id != id)
+            .collect();
+
+        Ok(list)
+    }
+
+    async fn get_comments(&self, id: String, page: u32) -> Result<PageList<ComicComment>> {
+        const PAGE_SIZE: usize = 10;
+
+        #[derive(Deserialize)]
+        struct RawComment {
+            id: u64,
+            #[serde(default)]
+            user_name: String,
+            #[serde(default)]
+            avatar: String,
+            #[serde(default)]
+            content: String,
+            #[serde(default)]
+            add_time: String,
+            #[serde(default)]
+            support_count: u32,
+            #[serde(default)]
+            reply_count: u32,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CommentResp {
+            #[serde(default)]
+            comment_ids: Vec<String>,
+            #[serde(default)]
+            comments: HashMap<String, RawComment>,
+            #[serde(default)]
+            total: usize,
+        }
+
+        let page = page.max(1);
+        let body = fetch(format!(
+            "https://www.manhuagui.com/tools/submit_ajax.ashx?action=comment_list&book_id={id}&page_index={page}"
+        ))
+        .await?
+        .text()
+        .await?;
+        let mut resp = serde_json::from_str::<CommentResp>(&body)?;
+
+        // `commentIds` carries the display order, while `comments` also holds
+        // the quoted comments of replies
+        let list = resp
+            .comment_ids
+            .iter()
+            .filter_map(|comment_id| {
+                let id = comment_id.split(',').next()?;
+                resp.comments.remove(id)
+            })
+            .map(|comment| {
+                let mut avatar = comment.avatar;
+                if avatar.starts_with("//") {
+                    avatar = format!("https:{avatar}");
+                }
+                ComicComment {
+                    id: comment.id.to_string(),
+                    user_name: comment.user_name,
+                    avatar,
+                    content: comment.content,
+                    created_at: comment.add_time,
+                    like_count: comment.support_count,
+                    reply_count: comment.reply_count,
+                }
+            })
+            .collect();
+
+        let has_more = (page as usize - 1) * PAGE_SIZE + resp.comment_ids.len() < resp.total;
+
+        Ok(PageList {
+            list,
+            page,
+            has_more,
+        })
+    }
+
+    fn browse_filters(&self) -> Vec<BrowseFilterGroup> {
+        fn group(key: &str, name: &str, options: &[(&str, &str)]) -> BrowseFilterGroup {
+            BrowseFilterGroup {
+                key: key.to_string(),
+                name: name.to_string(),
+                options: options
+                    .iter()
+                    .map(|(value, name)| BrowseFilterOption {
+                        value: value.to_string(),
+                        name: name.to_string(),
+                    })
+                    .collect(),
+            }
+        }
+
+        let letters = ('a'..='z')
+            .map(|c| (c.to_string(), c.to_ascii_uppercase().to_string()))
+            .chain([("0-9".to_string(), "0-9".to_string())])
+            .collect::<Vec<_>>();
+        let letters = letters
+            .iter()
+            .map(|(value, name)| (value.as_str(), name.as_str()))
+            .collect::<Vec<_>>();
+
+        vec![
+            group(
+                "region",
+                "地区",
+                &[
+                    ("japan", "日本"),
+                    ("hongkong", "港台"),
+                    ("china", "内地"),
+                    ("korea", "韩国"),
+                    ("europe", "欧美"),
+                    ("other", "其它"),
+                ],
+            ),
+            group(
+                "genre",
+                "剧情",
+                &[
+                    ("rexue", "热血"),
+                    ("maoxian", "冒险"),
+                    ("mohuan", "魔幻"),
+                    ("shengui", "神鬼"),
+                    ("gaoxiao", "搞笑"),
+                    ("mengxi", "萌系"),
+                    ("aiqing", "爱情"),
+                    ("kehuan", "科幻"),
+                    ("mofa", "魔法"),
+                    ("gedou", "格斗"),
+                    ("wuxia", "武侠"),
+                    ("jizhan", "机战"),
+                    ("zhanzheng", "战争"),
+                    ("jingji", "竞技"),
+                    ("tiyu", "体育"),
+                    ("xiaoyuan", "校园"),
+                    ("shenghuo", "生活"),
+                    ("lizhi", "励志"),
+                    ("lishi", "历史"),
+                    ("weiniang", "伪娘"),
+                    ("zhainan", "宅男"),
+                    ("funv", "腐女"),
+                    ("danmei", "耽美"),
+                    ("baihe", "百合"),
+                    ("hougong", "后宫"),
+                    ("zhiyu", "治愈"),
+                    ("meishi", "美食"),
+                    ("tuili", "推理"),
+                    ("xuanyi", "悬疑"),
+                    ("kongbu", "恐怖"),
+                    ("sige", "四格"),
+                    ("zhichang", "职场"),
+                    ("zhentan", "侦探"),
+                    ("shehui", "社会"),
+                    ("yinyue", "音乐"),
+                    ("wudao", "舞蹈"),
+                    ("zazhi", "杂志"),
+                    ("heidao", "黑道"),
+                ],
+            ),
+            group("status", "进度", &[("lianzai", "连载"), ("wanjie", "完结")]),
+            group("letter", "字母", &letters),
+        ]
+    }
+}
+
+struct ManhuaguiDetail {
+    status: ComicStatus,
+    genres: Vec<String>,
+    region: String,
+    aliases: Vec<String>,
+    last_chapter_name: String,
+    last_update: String,
+}
+
+fn parse_manhuagui_detail(list: &Selection) -> ManhuaguiDetail {
+    let mut genres = vec![];
+    let mut region = String::new();
+    let mut aliases = vec![];
+
+    // fields are laid out as `<span><strong>label：</strong><a>value</a>...</span>`
+    // and their positions differ between comics, so match them by label
+    for span in list.select("li>span:has(strong)").iter() {
+        let label = span.select_single("strong").text();
+        let values = span
+            .select("a")
+            .iter()
+            .map(|a| a.text().trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>();
+
+        match label.trim().trim_end_matches(['：', ':']) {
+            "漫画剧情" => genres = values,
+            "漫画地区" => region = values.into_iter().next().unwrap_or_default(),
+            "漫画别名" => aliases = values,
+            _ => {}
+        }
+    }
+
+    let status_node = list.select_single("li.status");
+    let status = match status_node.select_single("span.red").text().trim() {
+        "连载中" => ComicStatus::Ongoing,
+        "已完结" => ComicStatus::Completed,
+        _ => ComicStatus::Unknown,
+    };
+    let last_update = status_node
+        .select("span.red")
+        .iter()
+        .nth(1)
+        .map(|node| node.text().trim().to_string())
+        .unwrap_or_default();
+    let last_chapter_name = status_node
+        .select_single("a.blue")
+        .text()
+        .trim()
+        .to_string();
+
+    ManhuaguiDetail {
+        status,
+        genres,
+        region,
+        aliases,
+        last_chapter_name,
+        last_update,
+    }
+}
+
+/// Ratings are loaded by the page through a separate vote API, which returns
+/// the number of 1-5 star votes.
+async fn get_manhuagui_rating(id: &str) -> Result<Option<f64>> {
+    #[derive(Deserialize)]
+    struct VoteData {
+        s1: u64,
+        s2: u64,
+        s3: u64,
+        s4: u64,
+        s5: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct VoteResp {
+        success: bool,
+        data: Option<VoteData>,
+    }
+
+    let body = fetch(format!(
+        "https://www.manhuagui.com/tools/vote.ashx?act=get&bid={id}"
+    ))
+    .await?
+    .text()
+    .await?;
+    let resp = serde_json::from_str::<VoteResp>(&body)?;
+
+    let Some(VoteData { s1, s2, s3, s4, s5 }) = resp.data.filter(|_| resp.success) else {
+        return Ok(None);
+    };
+
+    let total = s1 + s2 + s3 + s4 + s5;
+    if total == 0 {
+        return Ok(None);
+    }
+
+    let score = (s1 + s2 * 2 + s3 * 3 + s4 * 4 + s5 * 5) as f64 / total as f64 * 2.0;
+    Ok(Some((score * 10.0).round() / 10.0))
+}
+
+/// Parses the cover cards shared by list pages and the side boxes of comic
+/// pages, which only carry the id, name and cover.
+fn parse_manhuagui_list_item(item: &Selection) -> ComicBrief {
+    let link = item.select_single(r#"a[href^="/comic/"]"#);
+    let id = link
+        .attr_or("href", "")
+        .trim()
+        .trim_start_matches("/comic/")
+        .trim_end_matches("/")
+        .to_string();
+
+    // covers below the fold are lazy loaded through `data-src`
+    let img = item.select_single("img");
+    let mut cover = img
+        .attr("data-src")
+        .or_else(|| img.attr("src"))
+        .map(|src| src.trim().to_string())
+        .unwrap_or_default();
+    if cover.starts_with("//") {
+        cover = format!("https:{cover}");
+    }
+
+    let mut name = link.attr_or("title", "").trim().to_string();
+    if name.is_empty() {
+        name = link.text().trim().to_string();
+    }
+
+    ComicBrief {
+        id,
+        name,
+        cover,
+        author: vec![],
+        intro: "".to_string(),
+        pub_date: "".to_string(),
+    }
+}
+
+pub fn manhuagui_cover(id: &str) -> String {
+    format!("https://cf.mhgui.com/cpic/b/{id}.jpg")
 }
 
 struct BriefSelectors<'a> {
@@ -334,14 +1038,10 @@ struct BriefSelectors<'a> {
 }
 
 fn parse_brief(node: &Selection, selectors: &BriefSelectors, id: String) -> ComicBrief {
-    let name = node
-        .select_single(&selectors.name)
-        .text()
-        .trim()
-        .to_string();
+    let name = node.select_single(selectors.name).text().trim().to_string();
 
     let mut cover = node
-        .select_single(&selectors.cover)
+        .select_single(selectors.cover)
         .attr_or("src", "")
         .trim()
         .to_string();
@@ -350,20 +1050,20 @@ fn parse_brief(node: &Selection, selectors: &BriefSelectors, id: String) -> Comi
     }
 
     let author: Vec<String> = node
-        .select(&selectors.author)
+        .select(selectors.author)
         .iter()
         .map(|item| item.text().trim().to_string())
         .collect();
 
     let pub_date = node
-        .select_single(&selectors.pub_date)
+        .select_single(selectors.pub_date)
         .text()
         .trim()
         .trim_end_matches("年")
         .to_string();
 
     let intro = node
-        .select_single(&selectors.intro)
+        .select_single(selectors.intro)
         .text()
         .trim()
         .to_string();
@@ -0,0 +1,226 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, Utc};
+use entity::{chapter_read, comic_meta, read_event};
+use serde::Serialize;
+
+/// Gaps between two progress heartbeats longer than this are treated as a
+/// break rather than reading time.
+pub const MAX_HEARTBEAT_GAP_SECS: i64 = 5 * 60;
+
+const TOP_LIMIT: usize = 10;
+
+/// Seconds spent reading since the previous heartbeat.
+pub fn heartbeat_duration(last: Option<&read_event::Model>, now: DateTime<Utc>) -> i32 {
+    let Some(last) = last else {
+        return 0;
+    };
+
+    let gap = (now - last.created_at).num_seconds();
+    if (0..=MAX_HEARTBEAT_GAP_SECS).contains(&gap) {
+        gap as i32
+    } else {
+        0
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyStats {
+    pub date: NaiveDate,
+    pub chapters: usize,
+    pub pages: usize,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComicStats {
+    pub comic_id: String,
+    pub comic_name: String,
+    pub chapters: usize,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorStats {
+    pub name: String,
+    pub chapters: usize,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadingStats {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub chapters: usize,
+    pub pages: usize,
+    pub seconds: i64,
+    /// Consecutive days with reading activity ending at `to`.
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub started_chapters: usize,
+    pub completed_chapters: usize,
+    pub completion_rate: f64,
+    pub daily: Vec<DailyStats>,
+    pub top_comics: Vec<ComicStats>,
+    pub top_authors: Vec<AuthorStats>,
+}
+
+#[derive(Default)]
+struct Bucket {
+    chapters: HashSet<(String, String)>,
+    pages: HashSet<(String, String, i32)>,
+    seconds: i64,
+}
+
+/// Aggregates the read events and chapter progress between `from` and `to`,
+/// both inclusive days in the `offset` timezone.
+pub fn compute_stats(
+    from: NaiveDate,
+    to: NaiveDate,
+    offset: FixedOffset,
+    events: &[read_event::Model],
+    chapter_reads: &[chapter_read::Model],
+    metas: &[comic_meta::Model],
+) -> ReadingStats {
+    let mut days: BTreeMap<NaiveDate, Bucket> = BTreeMap::new();
+    let mut comics: HashMap<&str, (&str, Bucket)> = HashMap::new();
+
+    for event in events {
+        let date = event.created_at.with_timezone(&offset).date_naive();
+        if date < from || date > to {
+            continue;
+        }
+
+        let chapter = (event.comic_id.clone(), event.chapter_id.clone());
+        let page = (event.comic_id.clone(), event.chapter_id.clone(), event.page);
+
+        let day = days.entry(date).or_default();
+        day.chapters.insert(chapter.clone());
+        day.pages.insert(page);
+        day.seconds += event.duration as i64;
+
+        let (_, comic) = comics
+            .entry(&event.comic_id)
+            .or_insert((&event.comic_name, Bucket::default()));
+        comic.chapters.insert(chapter);
+        comic.seconds += event.duration as i64;
+    }
+
+    let daily = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| {
+            let day = days.get(&date);
+            DailyStats {
+                date,
+                chapters: day.map_or(0, |day| day.chapters.len()),
+                pages: day.map_or(0, |day| day.pages.len()),
+                seconds: day.map_or(0, |day| day.seconds),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut longest_streak = 0;
+    let mut streak = 0;
+    for day in &daily {
+        if day.pages > 0 {
+            streak += 1;
+            longest_streak = longest_streak.max(streak);
+        } else {
+            streak = 0;
+        }
+    }
+    // an empty `to` day only breaks the streak once it is over, so
+    // today's missing reading does not reset it in the morning
+    let current_streak = match daily.last() {
+        Some(last) if last.pages == 0 => {
+            let end = to.checked_sub_days(Days::new(1)).unwrap_or(to);
+            daily
+                .iter()
+                .rev()
+                .skip_while(|day| day.date > end)
+                .take_while(|day| day.pages > 0)
+                .count()
+        }
+        _ => streak,
+    };
+
+    let authors_by_comic = metas
+        .iter()
+        .map(|meta| {
+            let authors = meta
+                .author
+                .as_array()
+                .map(|list| {
+                    list.iter()
+                        .filter_map(|name| name.as_str().map(str::to_string))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            (meta.id.as_str(), authors)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut authors: HashMap<&str, AuthorStats> = HashMap::new();
+    for (comic_id, (_, bucket)) in &comics {
+        for name in authors_by_comic.get(comic_id).into_iter().flatten() {
+            let author = authors.entry(name).or_insert(AuthorStats {
+                name: name.clone(),
+                chapters: 0,
+                seconds: 0,
+            });
+            author.chapters += bucket.chapters.len();
+            author.seconds += bucket.seconds;
+        }
+    }
+
+    let mut top_comics = comics
+        .into_iter()
+        .map(|(comic_id, (comic_name, bucket))| ComicStats {
+            comic_id: comic_id.to_string(),
+            comic_name: comic_name.to_string(),
+            chapters: bucket.chapters.len(),
+            seconds: bucket.seconds,
+        })
+        .collect::<Vec<_>>();
+    top_comics.sort_by(|a, b| b.chapters.cmp(&a.chapters).then(b.seconds.cmp(&a.seconds)));
+    top_comics.truncate(TOP_LIMIT);
+
+    let mut top_authors = authors.into_values().collect::<Vec<_>>();
+    top_authors.sort_by(|a, b| b.chapters.cmp(&a.chapters).then(b.seconds.cmp(&a.seconds)));
+    top_authors.truncate(TOP_LIMIT);
+
+    let started = chapter_reads
+        .iter()
+        .filter(|item| {
+            let date = item.read_at.with_timezone(&offset).date_naive();
+            date >= from && date <= to
+        })
+        .collect::<Vec<_>>();
+    let completed_chapters = started.iter().filter(|item| item.completed).count();
+    let completion_rate = if started.is_empty() {
+        0.0
+    } else {
+        completed_chapters as f64 / started.len() as f64
+    };
+
+    ReadingStats {
+        from,
+        to,
+        chapters: days.values().map(|day| day.chapters.len()).sum(),
+        pages: days.values().map(|day| day.pages.len()).sum(),
+        seconds: days.values().map(|day| day.seconds).sum(),
+        current_streak,
+        longest_streak,
+        started_chapters: started.len(),
+        completed_chapters,
+        completion_rate,
+        daily,
+        top_comics,
+        top_authors,
+    }
+}
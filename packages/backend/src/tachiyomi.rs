@@ -0,0 +1,360 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Read,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use entity::{chapter_read, collection, collection_comic, lib_comic};
+use flate2::read::GzDecoder;
+use prost::Message;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait,
+};
+use serde::Serialize;
+
+use crate::{
+    backup::ImportCount,
+    db,
+    site::{manhuagui_cover, SiteId},
+};
+
+/// Names the Tachiyomi and Mihon extensions give to the sources we support.
+const MANHUAGUI_SOURCE_NAMES: [&str; 3] = ["漫画柜", "漫畫櫃", "manhuagui"];
+
+/// Largest backup accepted once decompressed, so a small gzip bomb cannot
+/// exhaust memory.
+const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+// Subset of the backup schema of Mihon, which older Tachiyomi backups are
+// compatible with. Unknown fields are skipped by the decoder.
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Backup {
+    #[prost(message, repeated, tag = "1")]
+    manga: Vec<BackupManga>,
+    #[prost(message, repeated, tag = "2")]
+    categories: Vec<BackupCategory>,
+    #[prost(message, repeated, tag = "101")]
+    sources: Vec<BackupSource>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct BackupManga {
+    #[prost(int64, tag = "1")]
+    source: i64,
+    #[prost(string, tag = "2")]
+    url: String,
+    #[prost(string, tag = "3")]
+    title: String,
+    #[prost(string, tag = "9")]
+    thumbnail_url: String,
+    /// Milliseconds since epoch.
+    #[prost(int64, tag = "13")]
+    date_added: i64,
+    #[prost(message, repeated, tag = "16")]
+    chapters: Vec<BackupChapter>,
+    /// The `order` of the categories the manga belongs to.
+    #[prost(int64, repeated, tag = "17")]
+    categories: Vec<i64>,
+    #[prost(bool, tag = "100")]
+    favorite: bool,
+    #[prost(message, repeated, tag = "104")]
+    history: Vec<BackupHistory>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct BackupChapter {
+    #[prost(string, tag = "1")]
+    url: String,
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(bool, tag = "4")]
+    read: bool,
+    #[prost(int64, tag = "6")]
+    last_page_read: i64,
+    /// Milliseconds since epoch.
+    #[prost(int64, tag = "7")]
+    date_fetch: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct BackupCategory {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int64, tag = "2")]
+    order: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct BackupHistory {
+    #[prost(string, tag = "1")]
+    url: String,
+    /// Milliseconds since epoch.
+    #[prost(int64, tag = "2")]
+    last_read: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct BackupSource {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int64, tag = "2")]
+    source_id: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedEntry {
+    pub title: String,
+    pub source: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TachiyomiImportSummary {
+    pub library: ImportCount,
+    pub collections: ImportCount,
+    pub chapter_reads: ImportCount,
+    /// Entries from sources without a matching site, which were not imported.
+    pub unsupported: Vec<UnsupportedEntry>,
+}
+
+/// Decodes a Tachiyomi or Mihon backup. Errors mean the upload itself is
+/// invalid.
+pub fn decode_backup(bytes: &[u8]) -> Result<Backup> {
+    // `.tachibk` and `.proto.gz` files are gzipped, but accept plain ones too
+    let backup = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut buf = Vec::new();
+        GzDecoder::new(bytes)
+            .take(MAX_DECOMPRESSED_SIZE + 1)
+            .read_to_end(&mut buf)
+            .map_err(|err| anyhow!("Invalid Tachiyomi backup: {err}"))?;
+        if buf.len() as u64 > MAX_DECOMPRESSED_SIZE {
+            return Err(anyhow!(
+                "Tachiyomi backup exceeds {} MiB once decompressed",
+                MAX_DECOMPRESSED_SIZE / 1024 / 1024
+            ));
+        }
+        Backup::decode(buf.as_slice())
+    } else {
+        Backup::decode(bytes)
+    };
+
+    backup.map_err(|err| anyhow!("Invalid Tachiyomi backup: {err}"))
+}
+
+/// `/comic/12345/` -> `12345`
+fn parse_comic_id(url: &str) -> Option<String> {
+    let id = url
+        .trim()
+        .trim_start_matches("https://www.manhuagui.com")
+        .trim_start_matches("/comic/")
+        .trim_end_matches('/');
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+/// `/comic/12345/67890.html` -> `67890`
+fn parse_chapter_id(url: &str) -> Option<String> {
+    let id = url.trim().trim_end_matches(".html").rsplit('/').next()?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+}
+
+fn from_millis(millis: i64) -> Option<DateTime<Utc>> {
+    (millis > 0)
+        .then(|| DateTime::from_timestamp_millis(millis))
+        .flatten()
+}
+
+/// Imports the library, categories and chapter progress of a Tachiyomi or
+/// Mihon backup. Existing comics and collections are kept, and chapters
+/// already completed here are never marked unread.
+pub async fn import_tachiyomi_backup(
+    db: &DatabaseConnection,
+    user_id: i32,
+    backup: Backup,
+) -> Result<TachiyomiImportSummary> {
+    let source_names = backup
+        .sources
+        .iter()
+        .map(|source| (source.source_id, source.name.as_str()))
+        .collect::<HashMap<_, _>>();
+    let is_manhuagui = |source: i64| {
+        source_names.get(&source).is_some_and(|name| {
+            let name = name.to_lowercase();
+            MANHUAGUI_SOURCE_NAMES
+                .iter()
+                .any(|source_name| name.contains(source_name))
+        })
+    };
+
+    let mut summary = TachiyomiImportSummary::default();
+    let now = Utc::now();
+    let txn = db.begin().await?;
+
+    let mut collection_ids = HashMap::new();
+    let mut next_sort_order = collection::Entity::find()
+        .filter(collection::Column::UserId.eq(user_id))
+        .select_only()
+        .column_as(collection::Column::SortOrder.max(), "max")
+        .into_tuple::<Option<i32>>()
+        .one(&txn)
+        .await?
+        .flatten()
+        .map_or(0, |order| order + 1);
+    let mut categories = backup.categories;
+    categories.sort_by_key(|category| category.order);
+    for category in categories {
+        let existing = collection::Entity::find()
+            .filter(collection::Column::UserId.eq(user_id))
+            .filter(collection::Column::Name.eq(&category.name))
+            .one(&txn)
+            .await?;
+        let id = match &existing {
+            Some(existing) => existing.id,
+            None => {
+                let item = collection::ActiveModel {
+                    user_id: Set(user_id),
+                    name: Set(category.name.clone()),
+                    sort_order: Set(next_sort_order),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await?;
+                next_sort_order += 1;
+                item.id
+            }
+        };
+        collection_ids.insert(category.order, id);
+        summary.collections.add(existing.is_none());
+    }
+
+    for manga in backup.manga {
+        let comic_id = if is_manhuagui(manga.source) {
+            parse_comic_id(&manga.url)
+        } else {
+            None
+        };
+        let Some(comic_id) = comic_id else {
+            summary.unsupported.push(UnsupportedEntry {
+                source: source_names
+                    .get(&manga.source)
+                    .map_or_else(|| manga.source.to_string(), |name| name.to_string()),
+                title: manga.title,
+            });
+            continue;
+        };
+
+        if manga.favorite {
+            let existing = lib_comic::Entity::find_by_id((user_id, comic_id.clone()))
+                .one(&txn)
+                .await?;
+            if existing.is_none() {
+                let cover = if manga.thumbnail_url.is_empty() {
+                    manhuagui_cover(&comic_id)
+                } else {
+                    manga.thumbnail_url.clone()
+                };
+                lib_comic::ActiveModel {
+                    user_id: Set(user_id),
+                    id: Set(comic_id.clone()),
+                    name: Set(manga.title.clone()),
+                    cover: Set(cover),
+                    created_at: Set(from_millis(manga.date_added).unwrap_or(now)),
+                    updated_at: Set(now),
+                    site: Set(SiteId::Manhuagui.as_str().to_string()),
+                }
+                .insert(&txn)
+                .await?;
+            }
+            summary.library.add(existing.is_none());
+
+            let memberships = collection_comic::Entity::find()
+                .filter(collection_comic::Column::UserId.eq(user_id))
+                .filter(collection_comic::Column::ComicId.eq(&comic_id))
+                .all(&txn)
+                .await?
+                .into_iter()
+                .map(|item| item.collection_id)
+                .collect::<HashSet<_>>();
+            for order in &manga.categories {
+                let Some(&collection_id) = collection_ids.get(order) else {
+                    continue;
+                };
+                if memberships.contains(&collection_id) {
+                    continue;
+                }
+                collection_comic::ActiveModel {
+                    collection_id: Set(collection_id),
+                    user_id: Set(user_id),
+                    comic_id: Set(comic_id.clone()),
+                    sort_order: Set(db::next_collection_comic_order(&txn, collection_id).await?),
+                    created_at: Set(now),
+                }
+                .insert(&txn)
+                .await?;
+            }
+        }
+
+        let last_reads = manga
+            .history
+            .iter()
+            .map(|item| (item.url.as_str(), item.last_read))
+            .collect::<HashMap<_, _>>();
+        let existing_reads = chapter_read::Entity::find()
+            .filter(chapter_read::Column::UserId.eq(user_id))
+            .filter(chapter_read::Column::ComicId.eq(&comic_id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|item| (item.chapter_id.clone(), item))
+            .collect::<HashMap<_, _>>();
+
+        for chapter in &manga.chapters {
+            if !chapter.read && chapter.last_page_read <= 0 {
+                continue;
+            }
+            let Some(chapter_id) = parse_chapter_id(&chapter.url) else {
+                summary.chapter_reads.add(false);
+                continue;
+            };
+            let existing = existing_reads.get(&chapter_id);
+            if existing.is_some_and(|item| item.completed || !chapter.read) {
+                summary.chapter_reads.add(false);
+                continue;
+            }
+
+            let last_read = last_reads
+                .get(chapter.url.as_str())
+                .and_then(|millis| from_millis(*millis));
+            let read_at = last_read
+                .or_else(|| from_millis(chapter.date_fetch))
+                .unwrap_or(now);
+            let item = chapter_read::ActiveModel {
+                user_id: Set(user_id),
+                comic_id: Set(comic_id.clone()),
+                chapter_id: Set(chapter_id),
+                chapter_name: Set(chapter.name.clone()),
+                page: Set(chapter.last_page_read.max(0) as i32),
+                total_pages: Set(0),
+                completed: Set(chapter.read),
+                read_at: Set(read_at),
+                created_at: Set(existing.map_or(now, |item| item.created_at)),
+                // without a history entry, the read flag was likely set in bulk
+                marked: Set(last_read.is_none()),
+            };
+            if existing.is_some() {
+                item.update(&txn).await?;
+            } else {
+                item.insert(&txn).await?;
+            }
+            summary.chapter_reads.add(true);
+        }
+    }
+
+    txn.commit().await?;
+    Ok(summary)
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use entity::login_attempt;
+use sea_orm::{DatabaseConnection, Set};
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+
+/// Failed logins allowed before an account is locked out. A successful login
+/// resets the count.
+const ACCOUNT_FREE_ATTEMPTS: usize = 5;
+
+/// Failed logins allowed from an address across all accounts. Successful
+/// logins do not reset it, so one valid account cannot be used to keep
+/// guessing others.
+const IP_FREE_ATTEMPTS: usize = 20;
+
+/// Doubled for every failure past the free attempts.
+const BASE_LOCKOUT: TimeDelta = TimeDelta::seconds(30);
+
+const MAX_LOCKOUT: TimeDelta = TimeDelta::hours(1);
+
+/// Failures older than this no longer count.
+const FAILURE_WINDOW: TimeDelta = TimeDelta::days(1);
+
+/// How long attempts are kept for auditing.
+pub const AUDIT_RETENTION: TimeDelta = TimeDelta::days(90);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LoginResult {
+    Success,
+    InvalidCredentials,
+    /// Rejected without checking the password.
+    Locked,
+}
+
+impl LoginResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginResult::Success => "success",
+            LoginResult::InvalidCredentials => "invalidCredentials",
+            LoginResult::Locked => "locked",
+        }
+    }
+}
+
+fn lockout(failures: usize, free_attempts: usize) -> Option<TimeDelta> {
+    let exceeded = failures.checked_sub(free_attempts)?;
+    let lockout = BASE_LOCKOUT * 2_i32.pow(exceeded.min(16) as u32);
+    Some(lockout.min(MAX_LOCKOUT))
+}
+
+/// Returns how much longer logins to `username` from `ip` are locked out.
+pub async fn check_lockout(
+    db: &DatabaseConnection,
+    username: &str,
+    ip: &str,
+) -> Result<Option<TimeDelta>> {
+    let now = Utc::now();
+    let since = now - FAILURE_WINDOW;
+    let is_failure =
+        |item: &login_attempt::Model| item.result == LoginResult::InvalidCredentials.as_str();
+
+    let account_attempts =
+        db::get_login_attempts_since(db, login_attempt::Column::Username, username, since).await?;
+    let account_failures = account_attempts
+        .iter()
+        .take_while(|item| is_failure(item))
+        .count();
+
+    let ip_attempts =
+        db::get_login_attempts_since(db, login_attempt::Column::Ip, ip, since).await?;
+    let ip_failures = ip_attempts.iter().filter(|item| is_failure(item)).count();
+
+    let locked_until = |attempts: &[login_attempt::Model], lockout: Option<TimeDelta>| {
+        let last_failure = attempts.iter().find(|item| is_failure(item))?;
+        Some(last_failure.created_at + lockout?)
+    };
+    let until = [
+        locked_until(
+            &account_attempts,
+            lockout(account_failures, ACCOUNT_FREE_ATTEMPTS),
+        ),
+        locked_until(&ip_attempts, lockout(ip_failures, IP_FREE_ATTEMPTS)),
+    ]
+    .into_iter()
+    .flatten()
+    .max();
+
+    Ok(until.filter(|until| *until > now).map(|until| until - now))
+}
+
+pub async fn record_attempt(
+    db: &DatabaseConnection,
+    username: &str,
+    ip: &str,
+    result: LoginResult,
+) -> Result<()> {
+    if result != LoginResult::Success {
+        tracing::warn!(
+            "failed login for `{username}` from {ip}: {}",
+            result.as_str()
+        );
+    }
+
+    db::add_login_attempt(
+        db,
+        login_attempt::ActiveModel {
+            username: Set(username.to_string()),
+            ip: Set(ip.to_string()),
+            result: Set(result.as_str().to_string()),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(())
+}
@@ -1,3 +1,14 @@
+let shareToken: string | null = null;
+
+/** Sent along with every request, so share links open without an account. */
+function setShareToken(token: string | null) {
+  shareToken = token;
+}
+
+function withShareToken(params?: Record<string, unknown>) {
+  return shareToken ? { ...params, share: shareToken } : params;
+}
+
 async function handleFetch(url: string, options?: RequestInit) {
   const res = await fetch(url, options);
 
@@ -18,7 +29,8 @@ async function handleFetch(url: string, options?: RequestInit) {
   return data.data;
 }
 
-function get(url: string, params?: Record<string, unknown>) {
+function get(url: string, rawParams?: Record<string, unknown>) {
+  const params = withShareToken(rawParams);
   let fullUrl = url;
   if (params) {
     const tranformed = Object.fromEntries(
@@ -41,4 +53,4 @@ function post(url: string, data?: unknown) {
   });
 }
 
-export { get, post };
+export { get, post, setShareToken, withShareToken };
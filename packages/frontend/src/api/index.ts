@@ -1,10 +1,8 @@
-import { get, post } from './fetch';
+import { get, post, withShareToken } from './fetch';
 import type {
   AddToLibraryReq,
   CheckInLibraryReq,
   CheckInLibraryResp,
-  CheckPasswordReq,
-  CheckPasswordResp,
   DeleteHistoryReq,
   GetChapterReq,
   GetChapterResp,
@@ -12,9 +10,24 @@ import type {
   GetComicHistoryResp,
   GetComicReq,
   GetComicResp,
+  GetCurrentUserResp,
+  GetHistoryReq,
   GetHistoryResp,
+  GetLibraryReq,
   GetLibraryResp,
+  CreateApiTokenReq,
+  CreateApiTokenResp,
+  CreateShareLinkReq,
+  CreateShareLinkResp,
+  GetApiTokensResp,
+  GetSessionsResp,
+  GetShareLinksResp,
+  LoginReq,
+  LoginResp,
   RemoveFromLibraryReq,
+  RevokeApiTokenReq,
+  RevokeSessionReq,
+  RevokeShareLinkReq,
   SearchComicReq,
   SearchComicResp,
   UpsertHistoryReq,
@@ -34,15 +47,56 @@ export function getChapter(params: GetChapterReq): Promise<GetChapterResp> {
 }
 
 export function proxyImage(url: string) {
-  return `${Endpoints.ProxyImage}?url=${encodeURIComponent(url)}`;
+  const params = new URLSearchParams(withShareToken({ url }) as Record<string, string>);
+  return `${Endpoints.ProxyImage}?${params}`;
 }
 
-export function checkPassword(data: CheckPasswordReq): Promise<CheckPasswordResp> {
-  return post(Endpoints.CheckPassword, data);
+export function login(data: LoginReq): Promise<LoginResp> {
+  return post(Endpoints.Login, data);
 }
 
-export function getHistory(): Promise<GetHistoryResp> {
-  return get(Endpoints.GetHistory);
+export function logout(): Promise<void> {
+  return post(Endpoints.Logout);
+}
+
+export function getCurrentUser(): Promise<GetCurrentUserResp> {
+  return get(Endpoints.GetCurrentUser);
+}
+
+export function getSessions(): Promise<GetSessionsResp> {
+  return get(Endpoints.GetSessions);
+}
+
+export function revokeSession(data: RevokeSessionReq): Promise<void> {
+  return post(Endpoints.RevokeSession, data);
+}
+
+export function getApiTokens(): Promise<GetApiTokensResp> {
+  return get(Endpoints.GetApiTokens);
+}
+
+export function createApiToken(data: CreateApiTokenReq): Promise<CreateApiTokenResp> {
+  return post(Endpoints.CreateApiToken, data);
+}
+
+export function revokeApiToken(data: RevokeApiTokenReq): Promise<void> {
+  return post(Endpoints.RevokeApiToken, data);
+}
+
+export function getShareLinks(): Promise<GetShareLinksResp> {
+  return get(Endpoints.GetShareLinks);
+}
+
+export function createShareLink(data: CreateShareLinkReq): Promise<CreateShareLinkResp> {
+  return post(Endpoints.CreateShareLink, data);
+}
+
+export function revokeShareLink(data: RevokeShareLinkReq): Promise<void> {
+  return post(Endpoints.RevokeShareLink, data);
+}
+
+export function getHistory(params?: GetHistoryReq): Promise<GetHistoryResp> {
+  return get(Endpoints.GetHistory, params);
 }
 
 export function upsertHistory(data: UpsertHistoryReq): Promise<void> {
@@ -53,8 +107,8 @@ export function deleteHistory(data: DeleteHistoryReq): Promise<void> {
   return post(Endpoints.DeleteHistory, data);
 }
 
-export function getLibrary(): Promise<GetLibraryResp> {
-  return get(Endpoints.GetLibrary);
+export function getLibrary(params?: GetLibraryReq): Promise<GetLibraryResp> {
+  return get(Endpoints.GetLibrary, params);
 }
 
 export function addToLibrary(data: AddToLibraryReq): Promise<void> {
@@ -5,7 +5,17 @@ export enum Endpoints {
   GetComic = `${EndpointPrefix}/get_comic`,
   GetChapter = `${EndpointPrefix}/get_chapter`,
   ProxyImage = `${EndpointPrefix}/proxy_image`,
-  CheckPassword = `${EndpointPrefix}/check_password`,
+  Login = `${EndpointPrefix}/login`,
+  Logout = `${EndpointPrefix}/logout`,
+  GetCurrentUser = `${EndpointPrefix}/get_current_user`,
+  GetSessions = `${EndpointPrefix}/get_sessions`,
+  RevokeSession = `${EndpointPrefix}/revoke_session`,
+  GetApiTokens = `${EndpointPrefix}/get_api_tokens`,
+  CreateApiToken = `${EndpointPrefix}/create_api_token`,
+  RevokeApiToken = `${EndpointPrefix}/revoke_api_token`,
+  GetShareLinks = `${EndpointPrefix}/get_share_links`,
+  CreateShareLink = `${EndpointPrefix}/create_share_link`,
+  RevokeShareLink = `${EndpointPrefix}/revoke_share_link`,
   GetHistory = `${EndpointPrefix}/get_history`,
   UpsertHistory = `${EndpointPrefix}/upsert_history`,
   DeleteHistory = `${EndpointPrefix}/delete_history`,
@@ -67,12 +77,95 @@ export type GetChapterReq = {
 
 export type GetChapterResp = ComicChapter;
 
-export type CheckPasswordReq = {
+export type User = {
+  id: number;
+  username: string;
+  role: 'admin' | 'member' | 'guest';
+  createdAt: string;
+  updatedAt: string;
+};
+
+export type LoginReq = {
+  username: string;
   password: string;
 };
 
-export type CheckPasswordResp = {
-  valid: boolean;
+export type LoginResp = User;
+
+export type GetCurrentUserResp = User;
+
+export type Session = {
+  id: number;
+  userId: number;
+  userAgent: string;
+  ip: string;
+  createdAt: string;
+  lastSeenAt: string;
+  expiresAt: string;
+  current: boolean;
+};
+
+export type GetSessionsResp = Session[];
+
+export type RevokeSessionReq = {
+  id: number;
+};
+
+export type ApiTokenScope = 'read' | 'library' | 'downloads' | 'admin';
+
+export type ApiToken = {
+  id: number;
+  userId: number;
+  name: string;
+  prefix: string;
+  scopes: ApiTokenScope[];
+  createdAt: string;
+  lastUsedAt: string | null;
+  expiresAt: string | null;
+};
+
+export type GetApiTokensResp = ApiToken[];
+
+export type CreateApiTokenReq = {
+  name: string;
+  scopes: ApiTokenScope[];
+  expiresInDays?: number;
+};
+
+export type CreateApiTokenResp = ApiToken & {
+  secret: string;
+};
+
+export type RevokeApiTokenReq = {
+  id: number;
+};
+
+export type ShareLink = {
+  id: number;
+  userId: number;
+  comicId: string;
+  comicName: string;
+  chapterId: string | null;
+  chapterName: string | null;
+  createdAt: string;
+  expiresAt: string;
+};
+
+export type GetShareLinksResp = ShareLink[];
+
+export type CreateShareLinkReq = {
+  comicId: string;
+  chapterId?: string;
+  expiresInDays?: number;
+};
+
+export type CreateShareLinkResp = ShareLink & {
+  token: string;
+  path: string;
+};
+
+export type RevokeShareLinkReq = {
+  id: number;
 };
 
 export type HistoryItem = {
@@ -86,7 +179,19 @@ export type HistoryItem = {
   updatedAt: string;
 };
 
-export type GetHistoryResp = HistoryItem[];
+export interface PageList<T> {
+  list: T[];
+  page: number;
+  hasMore: boolean;
+}
+
+export type GetHistoryReq = {
+  page?: number;
+  pageSize?: number;
+  keyword?: string;
+};
+
+export type GetHistoryResp = PageList<HistoryItem>;
 
 export type UpsertHistoryReq = {
   comicId: string;
@@ -115,11 +220,41 @@ export type LibComic = {
   id: string;
   name: string;
   cover: string;
+  site: string;
   createdAt: string;
   updatedAt: string;
 };
 
-export type GetLibraryResp = LibComic[];
+export type ComicStatus = 'ongoing' | 'completed' | 'unknown';
+
+export type LibraryItem = LibComic & {
+  status: ComicStatus;
+  lastChapterName: string;
+  lastUpdate: string;
+  chapterCount: number;
+  readCount: number;
+  unreadCount: number;
+  history?: HistoryItem;
+  rating?: number;
+  notes: string;
+  tags: string[];
+};
+
+export type GetLibraryReq = {
+  page?: number;
+  pageSize?: number;
+  sort?: 'name' | 'addedAt' | 'lastRead' | 'lastUpdate' | 'unreadCount' | 'rating';
+  order?: 'asc' | 'desc';
+  status?: ComicStatus;
+  site?: string;
+  collectionId?: number;
+  hasUnread?: boolean;
+  tag?: string;
+  minRating?: number;
+  keyword?: string;
+};
+
+export type GetLibraryResp = PageList<LibraryItem>;
 
 export type CheckInLibraryReq = {
   id: string;
@@ -1,16 +1,19 @@
-import { LocalStorageKey } from '@/types/const';
+import type { User } from '@/api/types';
 import { withRefs } from '@/utils/pinia';
 import { defineStore } from 'pinia';
 
 export const useGlobalStore = withRefs(
   defineStore('global', {
     state: () => ({
-      password: localStorage.getItem(LocalStorageKey.Password) ?? '',
+      user: null as User | null,
     }),
+    getters: {
+      // guests and visitors of share links can only read
+      readOnly: (state) => !state.user || state.user.role === 'guest',
+    },
     actions: {
-      setPassword(password: string) {
-        this.password = password;
-        localStorage.setItem(LocalStorageKey.Password, password);
+      setUser(user: User | null) {
+        this.user = user;
       },
     },
   }),
@@ -1,5 +1,4 @@
 export enum LocalStorageKey {
-  Password = 'comiya_password',
   GestureTipShowed = 'comiya_gesture_tip_showed',
   GestureReverse = 'comiya_gesture_reverse',
   ReadDirection = 'comiya_read_direction',
@@ -24,7 +24,7 @@ declare module 'vue-router/auto-routes' {
     '/comic/[comicId]/[chapterId]': RouteRecordInfo<'/comic/[comicId]/[chapterId]', '/comic/:comicId/:chapterId', { comicId: ParamValue<true>, chapterId: ParamValue<true> }, { comicId: ParamValue<false>, chapterId: ParamValue<false> }>,
     '/history': RouteRecordInfo<'/history', '/history', Record<never, never>, Record<never, never>>,
     '/library': RouteRecordInfo<'/library', '/library', Record<never, never>, Record<never, never>>,
-    '/password': RouteRecordInfo<'/password', '/password', Record<never, never>, Record<never, never>>,
+    '/login': RouteRecordInfo<'/login', '/login', Record<never, never>, Record<never, never>>,
     '/search': RouteRecordInfo<'/search', '/search', Record<never, never>, Record<never, never>>,
   }
 }